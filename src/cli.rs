@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::compression::Compression;
+use crate::entities::EntityPoolConfig;
+use crate::format::OutputFormat;
+use crate::incidents::Incident;
+use crate::latency::LatencyProfile;
+use crate::profiles::ServiceProfile;
+use crate::retention::RetentionPolicy;
+use crate::rotation::{RotationPolicy, RotationStrategy};
+use crate::scenario::Scenario;
+use crate::messages::LevelWeights;
+use crate::traces::Topology;
+use crate::traffic::TrafficProfile;
+use crate::generator::{
+    GenerationMode, GeneratorConfig, GeneratorState, ServiceSpec, Sink, default_service_names, start_generation,
+};
+
+#[derive(Parser)]
+#[command(name = "log-generator", version, about = "Generate realistic enterprise log files at scale")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the web UI and REST API (default when no subcommand is given)
+    Serve(ServeArgs),
+    /// Run a single generation job headless and exit when it finishes
+    Generate(Box<GenerateArgs>),
+    /// Run the job described by a scenario file (TOML, YAML or JSON) headless
+    Run(RunArgs),
+}
+
+#[derive(Parser)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, env = "LOG_GENERATOR_BIND", default_value = "0.0.0.0")]
+    pub bind: IpAddr,
+
+    /// Port to listen on
+    #[arg(long, env = "LOG_GENERATOR_PORT", default_value_t = 3000)]
+    pub port: u16,
+
+    /// Root directory jobs write their per-service log directories to
+    #[arg(long, env = "LOG_GENERATOR_OUTPUT_DIR", default_value = "logs")]
+    pub output_dir: PathBuf,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Scenario file; the extension (.toml, .yaml, .yml, .json) selects the format
+    scenario: PathBuf,
+
+    /// Root directory the scenario's `output_subdir` is resolved against
+    #[arg(long, env = "LOG_GENERATOR_OUTPUT_DIR", default_value = "logs")]
+    out: PathBuf,
+
+    /// Only validate the scenario, without generating anything
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Number of microservices to simulate
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=1000))]
+    services: u32,
+
+    /// Total log volume across all services, in MB
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    total_mb: u64,
+
+    /// Define one service explicitly instead of using --services, as a JSON
+    /// object such as '{"name":"gateway-service","weight":60,"file_max_size_mb":50}'
+    /// (repeatable; the list order is the service order)
+    #[arg(long = "service", value_name = "JSON", value_parser = parse_service_spec, conflicts_with = "services")]
+    service_specs: Vec<ServiceSpec>,
+
+    /// Maximum size of a single log file before rotation, in MB
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    file_max_mb: u64,
+
+    /// Rotation policy: a name (size, time, size_or_time) or a JSON object
+    /// such as '{"type":"size_or_time","interval":"hourly"}'
+    #[arg(long, value_parser = parse_rotation)]
+    rotation: Option<RotationPolicy>,
+
+    /// How files are archived; all but `timestamped` keep the active file
+    /// at a stable `app.log`
+    #[arg(long, value_enum, default_value_t = RotationStrategy::Timestamped)]
+    rotation_strategy: RotationStrategy,
+
+    /// Compress rotated archives in the background
+    #[arg(long, value_enum, default_value_t = Compression::None)]
+    compress: Compression,
+
+    /// Leave the newest archive uncompressed until the next rotation
+    #[arg(long, requires = "compress")]
+    delay_compress: bool,
+
+    /// Archive retention of every service, as a JSON object such as
+    /// '{"max_files":10,"max_age_seconds":86400,"max_total_size_mb":500}'
+    #[arg(long, value_name = "JSON", value_parser = parse_retention)]
+    retention: Option<RetentionPolicy>,
+
+    /// Directory the per-service log directories are written to
+    #[arg(long, env = "LOG_GENERATOR_OUTPUT_DIR", default_value = "logs")]
+    out: PathBuf,
+
+    /// Where lines are written (repeatable): rotating files under --out,
+    /// standard output, or both
+    #[arg(long = "sink", value_enum, default_values_t = [Sink::File])]
+    sinks: Vec<Sink>,
+
+    /// Output format for every service
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Override the format of one service, e.g. `gateway-service=combined` (repeatable)
+    #[arg(long = "service-format", value_name = "NAME=FORMAT", value_parser = parse_service_format)]
+    service_formats: Vec<(String, OutputFormat)>,
+
+    /// Relative level weights, e.g. `debug=100,info=500,fatal=1`; levels left
+    /// out keep their defaults (info=500, warn=10, error=3)
+    #[arg(long, value_name = "LEVEL=WEIGHT,...", value_parser = parse_level_weights)]
+    levels: Option<LevelWeights>,
+
+    /// Latency models, as a JSON object such as
+    /// '{"http":{"median_ms":60,"p99_ms":1200},"db":{"median_ms":5,"p99_ms":300,"slow_ms":250}}'
+    #[arg(long, value_name = "JSON", value_parser = parse_latency)]
+    latency: Option<LatencyProfile>,
+
+    /// Replace the profile of one service, e.g.
+    /// `search-service={"categories":{"http":60,"cache":40},"paths":["/api/v2/search"]}` (repeatable)
+    #[arg(long = "service-profile", value_name = "NAME=JSON", value_parser = parse_service_profile)]
+    service_profiles: Vec<(String, ServiceProfile)>,
+
+    /// Seed for byte-identical, reproducible output
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Start of a simulated time window (RFC 3339), e.g. 2026-09-01T00:00:00Z
+    #[arg(long)]
+    start: Option<DateTime<Utc>>,
+
+    /// End of the simulated time window (RFC 3339)
+    #[arg(long)]
+    end: Option<DateTime<Utc>>,
+
+    /// Events per second per service (simulated, or real time with --live)
+    #[arg(long)]
+    eps: Option<f64>,
+
+    /// Write at a real-time rate (--eps or --bps) instead of as fast as possible
+    #[arg(long)]
+    live: bool,
+
+    /// Live mode: bytes per second per service
+    #[arg(long, requires = "live")]
+    bps: Option<u64>,
+
+    /// Live mode: stop after this many seconds (runs until Ctrl-C otherwise)
+    #[arg(long, requires = "live")]
+    duration: Option<u64>,
+
+    /// Traffic profile: a name (flat, diurnal, business_hours, weekend_dip)
+    /// or a JSON object such as '{"type":"diurnal","peak_hour":15}'
+    #[arg(long, value_parser = parse_traffic_profile)]
+    traffic: Option<TrafficProfile>,
+
+    /// Schedule an incident, as a JSON object such as
+    /// '{"kind":"outage","services":["payment-service"],"start_offset_seconds":600,"duration_seconds":300}'
+    /// (repeatable)
+    #[arg(long = "incident", value_name = "JSON", value_parser = parse_incident)]
+    incidents: Vec<Incident>,
+
+    /// Service call graph for distributed traces, as a JSON object such as
+    /// '{"entry":"gateway-service","calls":{"gateway-service":["auth-service","order-service"]}}'
+    #[arg(long, value_name = "JSON", value_parser = parse_topology)]
+    topology: Option<Topology>,
+
+    /// Share of events (0.0-1.0) that start a start/end lifecycle pair
+    #[arg(long, default_value_t = 0.0)]
+    lifecycle_ratio: f64,
+
+    /// Attribute lines to a pool of this many users with stable IPs and agents
+    #[arg(long)]
+    users: Option<u32>,
+
+    /// Number of tenants the user pool is spread over
+    #[arg(long, requires = "users")]
+    tenants: Option<u32>,
+
+    /// Zipf exponent of user and tenant activity
+    #[arg(long, requires = "users")]
+    zipf_exponent: Option<f64>,
+}
+
+fn parse_service_format(s: &str) -> Result<(String, OutputFormat), String> {
+    let (name, format) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=FORMAT, got `{}`", s))?;
+    let format = OutputFormat::from_str(format, true)?;
+    Ok((name.to_string(), format))
+}
+
+fn parse_level_weights(s: &str) -> Result<LevelWeights, String> {
+    let mut weights = LevelWeights::default();
+    for pair in s.split(',') {
+        let (level, weight) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected LEVEL=WEIGHT, got `{}`", pair))?;
+        let weight = weight.trim().parse().map_err(|e| format!("invalid weight `{}`: {}", weight, e))?;
+        match level.trim().to_ascii_lowercase().as_str() {
+            "trace" => weights.trace = weight,
+            "debug" => weights.debug = weight,
+            "info" => weights.info = weight,
+            "warn" => weights.warn = weight,
+            "error" => weights.error = weight,
+            "fatal" => weights.fatal = weight,
+            other => return Err(format!("unknown level `{}`", other)),
+        }
+    }
+    Ok(weights)
+}
+
+fn parse_service_profile(s: &str) -> Result<(String, ServiceProfile), String> {
+    let (name, json) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=JSON, got `{}`", s))?;
+    let profile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok((name.to_string(), profile))
+}
+
+fn parse_service_spec(s: &str) -> Result<ServiceSpec, String> {
+    serde_json::from_str(s).map_err(|e| e.to_string())
+}
+
+fn parse_traffic_profile(s: &str) -> Result<TrafficProfile, String> {
+    let json = if s.trim_start().starts_with('{') {
+        s.to_string()
+    } else {
+        serde_json::json!({ "type": s }).to_string()
+    };
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+fn parse_rotation(s: &str) -> Result<RotationPolicy, String> {
+    let json = if s.trim_start().starts_with('{') {
+        s.to_string()
+    } else {
+        serde_json::json!({ "type": s }).to_string()
+    };
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+fn parse_retention(s: &str) -> Result<RetentionPolicy, String> {
+    serde_json::from_str(s).map_err(|e| e.to_string())
+}
+
+fn parse_incident(s: &str) -> Result<Incident, String> {
+    serde_json::from_str(s).map_err(|e| e.to_string())
+}
+
+fn parse_latency(s: &str) -> Result<LatencyProfile, String> {
+    serde_json::from_str(s).map_err(|e| e.to_string())
+}
+
+fn parse_topology(s: &str) -> Result<Topology, String> {
+    serde_json::from_str(s).map_err(|e| e.to_string())
+}
+
+/// Run a generation job to completion, printing progress to stderr.
+/// Returns the process exit code: non-zero if any service failed.
+pub async fn run_generate(args: GenerateArgs) -> i32 {
+    let num_services = if args.service_specs.is_empty() {
+        args.services
+    } else if args.service_specs.len() > 1000 {
+        eprintln!("error: at most 1000 services can be defined with --service");
+        return 2;
+    } else {
+        args.service_specs.len() as u32
+    };
+    let service_names = if args.service_specs.is_empty() {
+        default_service_names(num_services)
+    } else {
+        args.service_specs.iter().map(|spec| spec.name.clone()).collect()
+    };
+    let service_formats: HashMap<String, OutputFormat> = args.service_formats.into_iter().collect();
+    if let Some(unknown) = service_formats.keys().find(|name| !service_names.contains(name)) {
+        eprintln!("error: unknown service in --service-format: {}", unknown);
+        return 2;
+    }
+    let service_profiles: HashMap<String, ServiceProfile> = args.service_profiles.into_iter().collect();
+    if let Some(unknown) = service_profiles.keys().find(|name| !service_names.contains(name)) {
+        eprintln!("error: unknown service in --service-profile: {}", unknown);
+        return 2;
+    }
+
+    let mut config = GeneratorConfig {
+        num_services,
+        target_bytes: if args.live { 0 } else { args.total_mb * 1024 * 1024 },
+        file_max_bytes: args.file_max_mb * 1024 * 1024,
+        rotation: args.rotation.unwrap_or_default(),
+        rotation_strategy: args.rotation_strategy,
+        compression: args.compress,
+        delay_compress: args.delay_compress,
+        retention: args.retention.unwrap_or_default(),
+        service_retention: HashMap::new(),
+        output_dir: args.out,
+        sinks: args.sinks,
+        service_names,
+        format: args.format,
+        service_formats,
+        level_weights: args.levels.unwrap_or_default(),
+        service_level_weights: HashMap::new(),
+        latency: args.latency.unwrap_or_default(),
+        service_latency: HashMap::new(),
+        service_profiles,
+        service_weights: HashMap::new(),
+        service_file_max_bytes: HashMap::new(),
+        seed: args.seed,
+        start_time: args.start,
+        end_time: args.end,
+        events_per_second: args.eps,
+        mode: if args.live { GenerationMode::Live } else { GenerationMode::Batch },
+        bytes_per_second: args.bps,
+        duration_seconds: args.duration,
+        traffic_profile: args.traffic.unwrap_or_default(),
+        service_traffic_profiles: HashMap::new(),
+        incidents: args.incidents,
+        topology: args.topology,
+        lifecycle_ratio: args.lifecycle_ratio,
+        entities: args.users.map(|users| {
+            let defaults = EntityPoolConfig::default();
+            EntityPoolConfig {
+                users,
+                tenants: args.tenants.unwrap_or(defaults.tenants),
+                zipf_exponent: args.zipf_exponent.unwrap_or(defaults.zipf_exponent),
+            }
+        }),
+    };
+    if !args.service_specs.is_empty() {
+        config.set_services(args.service_specs);
+    }
+    if let Err(e) = config.validate() {
+        eprintln!("error: {}", e);
+        return 2;
+    }
+
+    run_job(config).await
+}
+
+/// Load, validate and run a scenario file. Returns the process exit code.
+pub async fn run_scenario(args: RunArgs) -> i32 {
+    let scenario = match Scenario::load(&args.scenario) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("error: {}: {}", args.scenario.display(), e);
+            return 2;
+        }
+    };
+    let name = scenario.name.clone().unwrap_or_else(|| args.scenario.display().to_string());
+    let config = match scenario.into_config(&args.out) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {}: {}", args.scenario.display(), e);
+            return 2;
+        }
+    };
+    if args.check {
+        eprintln!("Scenario {} is valid ({} services)", name, config.num_services);
+        return 0;
+    }
+    eprintln!("Running scenario {}", name);
+    run_job(config).await
+}
+
+/// Run a validated job to completion, printing progress to stderr.
+async fn run_job(config: GeneratorConfig) -> i32 {
+    let state = Arc::new(GeneratorState::new());
+    if config.mode == GenerationMode::Live {
+        eprintln!(
+            "Streaming logs live across {} services into {} (Ctrl-C to stop)",
+            config.num_services,
+            config.output_dir.display()
+        );
+    } else {
+        eprintln!(
+            "Generating {} MB of logs across {} services into {}",
+            config.target_bytes / (1024 * 1024),
+            config.num_services,
+            config.output_dir.display()
+        );
+    }
+    start_generation(config, Arc::clone(&state));
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("Stopping...");
+                state.cancel.store(true, Ordering::SeqCst);
+            }
+        }
+        let running = state.running.load(Ordering::SeqCst);
+        print_progress(&state);
+        if !running {
+            break;
+        }
+    }
+
+    let failed = state.services_failed.load(Ordering::SeqCst);
+    if failed > 0 {
+        eprintln!("{} service(s) failed", failed);
+        1
+    } else {
+        eprintln!("Done");
+        0
+    }
+}
+
+fn print_progress(state: &GeneratorState) {
+    let written = state.bytes_written.load(Ordering::Relaxed);
+    let on_disk = state.disk_bytes.load(Ordering::Relaxed);
+    let on_disk = if on_disk == written {
+        String::new()
+    } else {
+        format!(" ({:.1} MB on disk)", on_disk as f64 / (1024.0 * 1024.0))
+    };
+    if state.live.load(Ordering::SeqCst) {
+        let (bytes_per_second, events_per_second) = state.throughput();
+        eprintln!(
+            "[live] {:.1} events/s, {:.1} KB/s, {:.1} MB written{}",
+            events_per_second,
+            bytes_per_second / 1024.0,
+            written as f64 / (1024.0 * 1024.0),
+            on_disk,
+        );
+        return;
+    }
+    let target = state.target_bytes.load(Ordering::Relaxed);
+    let percent = if target > 0 {
+        (written as f64 / target as f64 * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+    eprintln!(
+        "[{:5.1}%] {:.1} / {:.1} MB{}, {} / {} services done",
+        percent,
+        written as f64 / (1024.0 * 1024.0),
+        target as f64 / (1024.0 * 1024.0),
+        on_disk,
+        state.services_done.load(Ordering::SeqCst),
+        state.services_total.load(Ordering::SeqCst),
+    );
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::Rng;
+
+use crate::traffic::TrafficProfile;
+
+/// Where simulated clocks start when a seeded job does not give a start time.
+/// Fixed so that the same seed always produces the same timestamps.
+pub fn default_epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Mean gap between two events of one service on a simulated clock when no
+/// rate is configured (~100 events per second).
+pub const DEFAULT_MEAN_GAP_MICROS: f64 = 10_000.0;
+
+/// Source of event timestamps for one service.
+pub enum Clock {
+    /// Real time: every event is stamped with `Utc::now()`.
+    Wall,
+    /// Simulated time that advances by a random, exponentially distributed
+    /// gap on every tick. Only the service RNG drives it, so it is reproducible.
+    /// `mean_gap_micros` is the gap at full rate; the traffic profile
+    /// stretches it when the rate is lower.
+    Simulated {
+        now: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        mean_gap_micros: f64,
+        profile: TrafficProfile,
+    },
+}
+
+impl Clock {
+    pub fn simulated(
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        mean_gap_micros: f64,
+        profile: TrafficProfile,
+    ) -> Self {
+        Clock::Simulated {
+            now: start,
+            end,
+            mean_gap_micros,
+            profile,
+        }
+    }
+
+    /// The current time without advancing the clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::Wall => Utc::now(),
+            Clock::Simulated { now, .. } => *now,
+        }
+    }
+
+    /// End of a simulated clock's time window, if it has one.
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Clock::Simulated { end, .. } => *end,
+            Clock::Wall => None,
+        }
+    }
+
+    /// Simulated time left until the end of the window, if there is one,
+    /// weighted by the traffic profile (see `TrafficProfile::weighted_micros`).
+    pub fn remaining_weighted_micros(&self) -> Option<f64> {
+        match self {
+            Clock::Simulated { now, end: Some(end), profile, .. } => Some(profile.weighted_micros(*now, *end)),
+            _ => None,
+        }
+    }
+
+    pub fn set_mean_gap_micros(&mut self, gap: f64) {
+        if let Clock::Simulated { mean_gap_micros, .. } = self {
+            *mean_gap_micros = gap;
+        }
+    }
+
+    /// Advance to the timestamp of the next event and return it, or `None`
+    /// once a simulated clock has passed the end of its window or of the
+    /// representable time range (at a very low event rate).
+    pub fn tick(&mut self, rng: &mut impl Rng) -> Option<DateTime<Utc>> {
+        match self {
+            Clock::Wall => Some(Utc::now()),
+            Clock::Simulated { now, end, mean_gap_micros, profile } => {
+                let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+                let mean = *mean_gap_micros / profile.multiplier(*now);
+                let gap = (-u.ln() * mean) as i64;
+                *now = now.checked_add_signed(Duration::microseconds(gap.max(1)))?;
+                match end {
+                    Some(end) if *now > *end => None,
+                    _ => Some(*now),
+                }
+            }
+        }
+    }
+}
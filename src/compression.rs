@@ -0,0 +1,179 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Codec rotated archives are compressed with. The active file is always
+/// written plain.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Suffix appended to a compressed archive's name.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+
+    /// Compress `src` into `dst`, returning the compressed size.
+    fn compress(self, src: &Path, dst: &Path) -> io::Result<u64> {
+        let mut input = BufReader::with_capacity(256 * 1024, File::open(src)?);
+        let output = BufWriter::with_capacity(256 * 1024, File::create(dst)?);
+        let mut output = match self {
+            Compression::None => unreachable!("archives are only compressed with a codec"),
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?
+            }
+            Compression::Zstd => {
+                let mut encoder = zstd::Encoder::new(output, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?
+            }
+        };
+        output.flush()?;
+        Ok(output.get_ref().metadata()?.len())
+    }
+}
+
+/// Work for a compression worker.
+enum Job {
+    Compress(PathBuf),
+    /// Acknowledge once every archive queued before it is compressed.
+    Sync(SyncSender<()>),
+}
+
+/// Compresses one service's rotated archives on a background thread, in the
+/// order they were rotated, like logrotate's `compress`.
+pub struct Compressor {
+    codec: Compression,
+    /// Leave the newest archive plain until the next rotation, like
+    /// logrotate's `delaycompress`, for readers still draining it.
+    delay: bool,
+    delayed: Option<PathBuf>,
+    sender: Option<Sender<Job>>,
+    worker: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Compressor {
+    /// Start a worker for `codec`. `disk_bytes` is the job's on-disk byte
+    /// counter, which shrinks as archives are replaced by smaller ones.
+    pub fn new(codec: Compression, delay: bool, disk_bytes: Arc<AtomicU64>) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let worker = thread::spawn(move || {
+            for job in receiver {
+                let path = match job {
+                    Job::Compress(path) => path,
+                    Job::Sync(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                let raw = fs::metadata(&path)?.len();
+                let compressed_path = compressed_path(&path, codec);
+                // Write under a temporary name so a half-written archive never
+                // looks complete
+                let mut partial = compressed_path.clone().into_os_string();
+                partial.push(".tmp");
+                let compressed = codec.compress(&path, Path::new(&partial))?;
+                // Count the archive before it appears under its final name,
+                // where retention may delete it
+                disk_bytes.fetch_add(compressed, Ordering::Relaxed);
+                fs::rename(&partial, &compressed_path)?;
+                fs::remove_file(&path)?;
+                disk_bytes.fetch_sub(raw, Ordering::Relaxed);
+            }
+            Ok(())
+        });
+        Self {
+            codec,
+            delay,
+            delayed: None,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Name an archive called `file_name` will end up with.
+    pub fn final_name(&self, file_name: &str) -> String {
+        compressed_path(Path::new(file_name), self.codec).to_string_lossy().into_owned()
+    }
+
+    /// Whether archives are left plain until the next rotation.
+    pub fn delays(&self) -> bool {
+        self.delay
+    }
+
+    /// Queue a freshly rotated archive.
+    pub fn archived(&mut self, path: PathBuf) {
+        let path = if self.delay {
+            match self.delayed.replace(path) {
+                Some(previous) => previous,
+                None => return,
+            }
+        } else {
+            path
+        };
+        self.compress(path);
+    }
+
+    /// Queue an archive regardless of any delay, for callers that track the
+    /// delayed archive themselves because they rename archives.
+    pub fn compress(&self, path: PathBuf) {
+        if let Some(sender) = &self.sender {
+            // A send only fails once the worker has stopped on an error,
+            // which `finish` reports
+            let _ = sender.send(Job::Compress(path));
+        }
+    }
+
+    /// Block until every queued archive is compressed, so archives can be
+    /// renamed without the worker losing track of them.
+    pub fn wait_idle(&self) {
+        if let Some(sender) = &self.sender {
+            let (done, ack) = mpsc::sync_channel(1);
+            if sender.send(Job::Sync(done)).is_ok() {
+                // Fails only if the worker stopped on an error
+                let _ = ack.recv();
+            }
+        }
+    }
+
+    /// Compress any delayed archive and wait for the worker to finish.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(path) = self.delayed.take() {
+            self.compress(path);
+        }
+        drop(self.sender.take());
+        match self.worker.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(io::Error::other("compression worker panicked")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn compressed_path(path: &Path, codec: Compression) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    if let Some(extension) = codec.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    PathBuf::from(name)
+}
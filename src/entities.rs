@@ -0,0 +1,166 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Zipf};
+use serde::Deserialize;
+
+use crate::messages::{self, FieldValue, LogEvent};
+
+/// Size and skew of the job-wide user and tenant populations.
+#[derive(Clone, Deserialize)]
+pub struct EntityPoolConfig {
+    #[serde(default = "default_users")]
+    pub users: u32,
+    #[serde(default = "default_tenants")]
+    pub tenants: u32,
+    /// Zipf exponent; higher values concentrate activity on fewer users and
+    /// tenants.
+    #[serde(default = "default_zipf_exponent")]
+    pub zipf_exponent: f64,
+}
+
+fn default_users() -> u32 {
+    1000
+}
+
+fn default_tenants() -> u32 {
+    50
+}
+
+fn default_zipf_exponent() -> f64 {
+    1.1
+}
+
+impl Default for EntityPoolConfig {
+    fn default() -> Self {
+        Self {
+            users: default_users(),
+            tenants: default_tenants(),
+            zipf_exponent: default_zipf_exponent(),
+        }
+    }
+}
+
+/// Upper bound on pool sizes; each user is kept in memory for the whole job.
+const MAX_POOL_SIZE: u32 = 1_000_000;
+
+impl EntityPoolConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_POOL_SIZE).contains(&self.users) || !(1..=MAX_POOL_SIZE).contains(&self.tenants) {
+            return Err(format!("entities.users and entities.tenants must be between 1 and {}", MAX_POOL_SIZE));
+        }
+        if !(self.zipf_exponent > 0.0 && self.zipf_exponent.is_finite()) {
+            return Err("entities.zipf_exponent must be greater than 0".into());
+        }
+        Ok(())
+    }
+}
+
+/// How long a user's session lasts before a new session id is issued.
+const SESSION_SECONDS: i64 = 30 * 60;
+
+const SURNAMES: &[&str] = &[
+    "smith", "johnson", "garcia", "miller", "davis", "martinez", "lopez",
+    "wilson", "anderson", "thomas", "taylor", "moore", "jackson", "lee",
+    "walker", "hall", "young", "king", "wright", "scott", "nguyen", "patel",
+    "kim", "chen", "mueller", "rossi", "silva", "tanaka", "novak", "berg",
+];
+
+const TENANT_NAMES: &[&str] = &[
+    "acme", "globex", "initech", "umbrella", "hooli", "stark", "wayne",
+    "wonka", "cyberdyne", "tyrell", "soylent", "aperture", "vandelay",
+    "oscorp", "massive-dynamic", "pied-piper",
+];
+
+/// A user whose identity stays the same on every line they appear in.
+struct User {
+    id: uuid::Uuid,
+    username: String,
+    ip: String,
+    user_agent: &'static str,
+    auth_provider: &'static str,
+    tenant: usize,
+    /// Offsets the user's session boundaries and seeds their session ids.
+    session_seed: u64,
+}
+
+/// Users and tenants shared by every service of a job. Lines pick users with
+/// a Zipf distribution, so a few users account for most of the activity.
+pub struct EntityPool {
+    users: Vec<User>,
+    tenants: Vec<String>,
+    user_ranks: Zipf<f64>,
+}
+
+impl EntityPool {
+    pub fn new(config: &EntityPoolConfig, seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let tenants = (0..config.tenants as usize)
+            .map(|i| match TENANT_NAMES.get(i) {
+                Some(name) => name.to_string(),
+                None => format!("tenant-{}", i + 1),
+            })
+            .collect();
+        // Large tenants have many users, following the same skew
+        let tenant_ranks = Zipf::new(config.tenants as u64, config.zipf_exponent).expect("validated exponent");
+        let users = (0..config.users)
+            .map(|i| User {
+                id: messages::random_uuid(&mut rng),
+                username: format!(
+                    "{}{}{}",
+                    rng.gen_range(b'a'..=b'z') as char,
+                    messages::pick(&mut rng, SURNAMES),
+                    i + 1
+                ),
+                ip: messages::random_ip(&mut rng),
+                user_agent: messages::pick(&mut rng, messages::USER_AGENTS),
+                auth_provider: messages::pick(&mut rng, messages::AUTH_PROVIDERS),
+                tenant: tenant_ranks.sample(&mut rng) as usize - 1,
+                session_seed: rng.gen(),
+            })
+            .collect();
+        Self {
+            users,
+            tenants,
+            user_ranks: Zipf::new(config.users as u64, config.zipf_exponent).expect("validated exponent"),
+        }
+    }
+
+    /// Pick a user, favouring the most active ones.
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        self.user_ranks.sample(rng) as usize - 1
+    }
+
+    /// Replace the identity fields of `event` (user id, client IP, user
+    /// agent, auth provider, remote user) with those of user `index`, and tag
+    /// lines that name a user with their tenant and current session.
+    pub fn apply(&self, index: usize, event: &mut LogEvent) {
+        let user = &self.users[index];
+        let mut identified = false;
+        for (key, value) in event.fields.iter_mut() {
+            match *key {
+                "user_id" => {
+                    *value = user.id.into();
+                    identified = true;
+                }
+                "client_ip" => {
+                    *value = user.ip.clone().into();
+                    identified = true;
+                }
+                "user_agent" => *value = user.user_agent.into(),
+                "auth_provider" => *value = user.auth_provider.into(),
+                // Most access-log lines are anonymous; keep those that are
+                "remote_user" if !matches!(value, FieldValue::Str(name) if name == "-") => {
+                    *value = user.username.clone().into();
+                }
+                _ => {}
+            }
+        }
+        if identified {
+            let offset = (user.session_seed % SESSION_SECONDS as u64) as i64;
+            let session = (event.timestamp.timestamp() + offset).div_euclid(SESSION_SECONDS) as u64;
+            let session_id = SmallRng::seed_from_u64(user.session_seed ^ session).gen::<u64>();
+            event.set_field("tenant_id", self.tenants[user.tenant].as_str().into());
+            event.set_field("session_id", format!("{:016x}", session_id).into());
+        }
+    }
+}
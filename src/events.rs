@@ -0,0 +1,55 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Name of the job event log in the output directory.
+pub const EVENT_LOG_FILE_NAME: &str = "events.jsonl";
+
+/// Something that happened to a job's files, such as an archive deleted by
+/// retention.
+#[derive(Serialize)]
+pub struct JobEvent<'a> {
+    /// Service clock time of the event (simulated in backfills).
+    pub timestamp: DateTime<Utc>,
+    pub service: &'a str,
+    pub event: &'static str,
+    pub file: &'a str,
+    pub bytes: u64,
+    /// Which limit caused the event.
+    pub reason: &'static str,
+}
+
+/// JSONL log of job events, shared by every service of a job. Like the label
+/// sidecar it is only created once the first event is recorded, and each
+/// entry is flushed immediately so it can be followed during a run.
+pub struct JobEventLog {
+    path: PathBuf,
+    writer: Mutex<Option<BufWriter<File>>>,
+}
+
+impl JobEventLog {
+    pub fn new(output_dir: &Path) -> Self {
+        Self {
+            path: output_dir.join(EVENT_LOG_FILE_NAME),
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn record(&self, event: &JobEvent) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        if writer.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            *writer = Some(BufWriter::new(File::create(&self.path)?));
+        }
+        let writer = writer.as_mut().unwrap();
+        serde_json::to_writer(&mut *writer, event)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
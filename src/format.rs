@@ -0,0 +1,377 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::messages::{FieldValue, LogEvent};
+
+/// Output encoding for generated log lines.
+#[derive(Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// `timestamp [LEVEL] [service] [trace_id=..] ... template | k=v ...`
+    #[default]
+    Text,
+    /// One JSON object per line with typed detail fields.
+    Json,
+    /// Single-line `key=value` pairs as used by Heroku/Go loggers.
+    Logfmt,
+    /// IETF syslog (RFC 5424) with trace ids in structured data.
+    Rfc5424,
+    /// Legacy BSD syslog (RFC 3164).
+    Rfc3164,
+    /// Apache Common Log Format.
+    Clf,
+    /// Apache/Nginx Combined Log Format (CLF plus referer and user agent).
+    Combined,
+    /// Combined Log Format followed by nginx's `$request_time` in seconds.
+    Nginx,
+}
+
+impl OutputFormat {
+    /// Access-log formats only describe served HTTP requests, so services
+    /// using them generate request events instead of application events.
+    pub fn is_access_log(self) -> bool {
+        matches!(self, OutputFormat::Clf | OutputFormat::Combined | OutputFormat::Nginx)
+    }
+
+    /// Build a formatter for one service. `hostname` and `procid` are only
+    /// used by the syslog encodings.
+    pub fn formatter(self, hostname: &str, procid: u32) -> Box<dyn LogFormatter> {
+        match self {
+            OutputFormat::Text => Box::new(TextFormatter),
+            OutputFormat::Json => Box::new(JsonFormatter),
+            OutputFormat::Logfmt => Box::new(LogfmtFormatter),
+            OutputFormat::Rfc5424 => Box::new(Rfc5424Formatter {
+                hostname: hostname.to_string(),
+                procid,
+            }),
+            OutputFormat::Rfc3164 => Box::new(Rfc3164Formatter {
+                hostname: hostname.to_string(),
+                procid,
+            }),
+            OutputFormat::Clf => Box::new(AccessLogFormatter { combined: false, request_time: false }),
+            OutputFormat::Combined => Box::new(AccessLogFormatter { combined: true, request_time: false }),
+            OutputFormat::Nginx => Box::new(AccessLogFormatter { combined: true, request_time: true }),
+        }
+    }
+}
+
+/// Renders a structured `LogEvent` into a single log record.
+pub trait LogFormatter: Send {
+    /// Append the rendered event to `out` (without a trailing newline).
+    fn format(&self, event: &LogEvent, out: &mut String);
+}
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// The original bracketed layout with a flattened `k=v` detail section.
+pub struct TextFormatter;
+
+impl LogFormatter for TextFormatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        let _ = write!(
+            out,
+            "{} [{}] [{}] [trace_id={}] [span_id={}] [thread=worker-{}] {} |",
+            event.timestamp.format(TIMESTAMP_FORMAT),
+            event.level.as_str(),
+            event.service,
+            event.trace_id,
+            event.span_id,
+            event.thread,
+            event.template,
+        );
+        push_text_fields(out, event);
+        if let Some(frames) = &event.stack_trace {
+            out.push_str("\n  Stacktrace:\n");
+            for frame in frames {
+                out.push_str("    ");
+                out.push_str(frame);
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// JSON Lines: header fields and detail fields as top-level typed keys.
+pub struct JsonFormatter;
+
+impl LogFormatter for JsonFormatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        out.push('{');
+        push_json_key(out, "timestamp");
+        let _ = write!(out, "\"{}\"", event.timestamp.format(TIMESTAMP_FORMAT));
+        for (key, value) in [
+            ("level", event.level.as_str()),
+            ("service", event.service),
+            ("trace_id", &event.trace_id),
+            ("span_id", &event.span_id),
+        ] {
+            out.push(',');
+            push_json_key(out, key);
+            push_json_str(out, value);
+        }
+        let _ = write!(out, ",\"thread\":\"worker-{}\"", event.thread);
+        out.push(',');
+        push_json_key(out, "message");
+        push_json_str(out, event.template);
+        for (key, value) in &event.fields {
+            out.push(',');
+            push_json_key(out, key);
+            push_json_value(out, value);
+        }
+        if let Some(frames) = &event.stack_trace {
+            out.push(',');
+            push_json_key(out, "stack_trace");
+            out.push('[');
+            for (i, frame) in frames.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_json_str(out, frame);
+            }
+            out.push(']');
+        }
+        out.push('}');
+    }
+}
+
+/// logfmt: every attribute, including the header, as `key=value` on one line.
+pub struct LogfmtFormatter;
+
+impl LogFormatter for LogfmtFormatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        let _ = write!(
+            out,
+            "ts={} level={} service={} trace_id={} span_id={} thread=worker-{} msg=",
+            event.timestamp.format(TIMESTAMP_FORMAT),
+            event.level.as_str().to_ascii_lowercase(),
+            event.service,
+            event.trace_id,
+            event.span_id,
+            event.thread,
+        );
+        push_quoted(out, event.template);
+        for (key, value) in &event.fields {
+            out.push(' ');
+            out.push_str(key);
+            out.push('=');
+            match value {
+                FieldValue::Percent(p) => {
+                    let _ = write!(out, "{}", p);
+                }
+                _ => push_text_value(out, value),
+            }
+        }
+        push_inline_stack(out, event);
+    }
+}
+
+/// Syslog facility used for every generated message (local0).
+const SYSLOG_FACILITY: u8 = 16;
+
+/// Private enterprise number reserved for documentation (RFC 5612), used as
+/// the SD-ID suffix of the structured data element.
+const SD_ENTERPRISE_ID: u32 = 32473;
+
+fn syslog_pri(event: &LogEvent) -> u8 {
+    SYSLOG_FACILITY * 8 + event.level.syslog_severity()
+}
+
+/// RFC 5424: `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG`
+pub struct Rfc5424Formatter {
+    hostname: String,
+    procid: u32,
+}
+
+impl LogFormatter for Rfc5424Formatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        let _ = write!(
+            out,
+            "<{}>1 {} {} {} {} {} [trace@{} trace_id=\"{}\" span_id=\"{}\" thread=\"worker-{}\"] {} |",
+            syslog_pri(event),
+            event.timestamp.format(TIMESTAMP_FORMAT),
+            truncate(&self.hostname, 255),
+            truncate(event.service, 48),
+            self.procid,
+            truncate(event.category, 32),
+            SD_ENTERPRISE_ID,
+            event.trace_id,
+            event.span_id,
+            event.thread,
+            event.template,
+        );
+        push_text_fields(out, event);
+        push_inline_stack(out, event);
+    }
+}
+
+/// RFC 3164: `<PRI>Mmm dd hh:mm:ss HOSTNAME TAG[PID]: MSG`
+pub struct Rfc3164Formatter {
+    hostname: String,
+    procid: u32,
+}
+
+impl LogFormatter for Rfc3164Formatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        let _ = write!(
+            out,
+            "<{}>{} {} {}[{}]: [trace_id={}] [span_id={}] {} |",
+            syslog_pri(event),
+            event.timestamp.format("%b %e %H:%M:%S"),
+            self.hostname,
+            truncate(event.service, 32),
+            self.procid,
+            event.trace_id,
+            event.span_id,
+            event.template,
+        );
+        push_text_fields(out, event);
+        push_inline_stack(out, event);
+    }
+}
+
+/// Common/Combined Log Format, rendered from an HTTP request event:
+/// `host ident authuser [date] "request" status bytes ["referer" "user-agent"] [request_time]`
+pub struct AccessLogFormatter {
+    combined: bool,
+    request_time: bool,
+}
+
+impl LogFormatter for AccessLogFormatter {
+    fn format(&self, event: &LogEvent, out: &mut String) {
+        let _ = write!(
+            out,
+            "{} - {} [{}] \"{} {} {}\" ",
+            field_str(event, "client_ip"),
+            field_str(event, "remote_user"),
+            event.timestamp.format("%d/%b/%Y:%H:%M:%S %z"),
+            field_str(event, "method"),
+            field_str(event, "path"),
+            field_str(event, "protocol"),
+        );
+        push_field(out, event.field("status"));
+        out.push(' ');
+        match event.field("response_bytes") {
+            Some(FieldValue::Int(0)) | None => out.push('-'),
+            bytes => push_field(out, bytes),
+        }
+        if self.combined {
+            let _ = write!(
+                out,
+                " \"{}\" \"{}\"",
+                field_str(event, "referer"),
+                field_str(event, "user_agent"),
+            );
+        }
+        if self.request_time {
+            let latency_ms = match event.field("latency_ms") {
+                Some(FieldValue::Int(ms)) => *ms,
+                _ => 0,
+            };
+            let _ = write!(out, " {}.{:03}", latency_ms / 1000, latency_ms % 1000);
+        }
+    }
+}
+
+/// A string field's value, or `-` (the access-log placeholder) when absent.
+fn field_str<'e>(event: &'e LogEvent, key: &str) -> &'e str {
+    match event.field(key) {
+        Some(FieldValue::Str(s)) => s,
+        _ => "-",
+    }
+}
+
+fn push_field(out: &mut String, value: Option<&FieldValue>) {
+    match value {
+        Some(value) => push_text_value(out, value),
+        None => out.push('-'),
+    }
+}
+
+/// Longest prefix of `s` with at most `max` bytes (syslog header field limits).
+fn truncate(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn push_text_fields(out: &mut String, event: &LogEvent) {
+    for (key, value) in &event.fields {
+        out.push(' ');
+        out.push_str(key);
+        out.push('=');
+        push_text_value(out, value);
+    }
+}
+
+/// Single-line encodings fold the stack frames into one quoted value.
+fn push_inline_stack(out: &mut String, event: &LogEvent) {
+    if let Some(frames) = &event.stack_trace {
+        out.push_str(" stack_trace=");
+        push_quoted(out, &frames.join("\n"));
+    }
+}
+
+fn push_text_value(out: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(s) if s.contains([' ', '"', '=']) => push_quoted(out, s),
+        FieldValue::Str(s) => out.push_str(s),
+        FieldValue::Int(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        FieldValue::Bool(b) => {
+            let _ = write!(out, "{}", b);
+        }
+        FieldValue::Percent(p) if p.fract() == 0.0 => {
+            let _ = write!(out, "{}%", *p as i64);
+        }
+        FieldValue::Percent(p) => {
+            let _ = write!(out, "{:.2}%", p);
+        }
+    }
+}
+
+/// Double-quote a value, escaping quotes, backslashes and newlines.
+fn push_quoted(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn push_json_key(out: &mut String, key: &str) {
+    push_json_str(out, key);
+    out.push(':');
+}
+
+fn push_json_str(out: &mut String, s: &str) {
+    // serde_json handles escaping of quotes, backslashes and control characters
+    out.push_str(&serde_json::to_string(s).unwrap_or_default());
+}
+
+fn push_json_value(out: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(s) => push_json_str(out, s),
+        FieldValue::Int(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        FieldValue::Bool(b) => {
+            let _ = write!(out, "{}", b);
+        }
+        FieldValue::Percent(p) => {
+            let _ = write!(out, "{}", p);
+        }
+    }
+}
@@ -1,53 +1,552 @@
+use std::collections::HashMap;
+use std::io::Write;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use rand::SeedableRng;
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
 use rand::rngs::SmallRng;
+use serde::Deserialize;
 use tokio::task;
 
-use crate::messages::{self, LogLevel};
-use crate::rotation::RotatingWriter;
+use crate::clock::{self, Clock};
+use crate::compression::{Compression, Compressor};
+use crate::entities::{EntityPool, EntityPoolConfig};
+use crate::events::JobEventLog;
+use crate::format::{LogFormatter, OutputFormat};
+use crate::incidents::{Incident, IncidentSchedule};
+use crate::labels::Anomaly;
+use crate::latency::LatencyProfile;
+use crate::messages::{self, EventQueue, LevelWeights, LogEvent, LogLevel, ServiceContext};
+use crate::profiles::ServiceProfile;
+use crate::retention::{Retention, RetentionPolicy};
+use crate::rotation::{RotatingWriter, RotationPolicy, RotationStrategy};
+use crate::traces::{Topology, TraceFeed};
+use crate::traffic::TrafficProfile;
 
 /// Shared state for tracking progress and cancellation.
 pub struct GeneratorState {
     pub bytes_written: AtomicU64,
+    /// Bytes the job's log files take on disk: `bytes_written`, less what
+    /// compression has saved. Shared with the compression workers.
+    pub disk_bytes: Arc<AtomicU64>,
     pub target_bytes: AtomicU64,
     pub running: AtomicBool,
     pub cancel: AtomicBool,
     pub services_total: AtomicU64,
     pub services_done: AtomicU64,
+    pub services_failed: AtomicU64,
+    pub events_written: AtomicU64,
+    pub live: AtomicBool,
+    throughput: Mutex<ThroughputSample>,
+}
+
+/// Last throughput measurement, refreshed at most once per second so the
+/// reported rate does not depend on how often progress is polled.
+struct ThroughputSample {
+    at: Instant,
+    bytes: u64,
+    events: u64,
+    bytes_per_second: f64,
+    events_per_second: f64,
+}
+
+impl ThroughputSample {
+    fn new() -> Self {
+        Self {
+            at: Instant::now(),
+            bytes: 0,
+            events: 0,
+            bytes_per_second: 0.0,
+            events_per_second: 0.0,
+        }
+    }
 }
 
 impl GeneratorState {
     pub fn new() -> Self {
         Self {
             bytes_written: AtomicU64::new(0),
+            disk_bytes: Arc::new(AtomicU64::new(0)),
             target_bytes: AtomicU64::new(0),
             running: AtomicBool::new(false),
             cancel: AtomicBool::new(false),
             services_total: AtomicU64::new(0),
             services_done: AtomicU64::new(0),
+            services_failed: AtomicU64::new(0),
+            events_written: AtomicU64::new(0),
+            live: AtomicBool::new(false),
+            throughput: Mutex::new(ThroughputSample::new()),
         }
     }
 
     pub fn reset(&self) {
         self.bytes_written.store(0, Ordering::SeqCst);
+        self.disk_bytes.store(0, Ordering::SeqCst);
         self.target_bytes.store(0, Ordering::SeqCst);
         self.running.store(false, Ordering::SeqCst);
         self.cancel.store(false, Ordering::SeqCst);
         self.services_total.store(0, Ordering::SeqCst);
         self.services_done.store(0, Ordering::SeqCst);
+        self.services_failed.store(0, Ordering::SeqCst);
+        self.events_written.store(0, Ordering::SeqCst);
+        self.live.store(false, Ordering::SeqCst);
+        *self.throughput.lock().unwrap() = ThroughputSample::new();
+    }
+
+    /// Current write rate as `(bytes per second, events per second)`.
+    pub fn throughput(&self) -> (f64, f64) {
+        let mut sample = self.throughput.lock().unwrap();
+        let elapsed = sample.at.elapsed().as_secs_f64();
+        if elapsed >= 1.0 {
+            let bytes = self.bytes_written.load(Ordering::Relaxed);
+            let events = self.events_written.load(Ordering::Relaxed);
+            sample.bytes_per_second = bytes.saturating_sub(sample.bytes) as f64 / elapsed;
+            sample.events_per_second = events.saturating_sub(sample.events) as f64 / elapsed;
+            sample.at = Instant::now();
+            sample.bytes = bytes;
+            sample.events = events;
+        }
+        (sample.bytes_per_second, sample.events_per_second)
     }
 }
 
+/// How a job decides how much to write and how fast.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GenerationMode {
+    /// Write as fast as possible until the volume target is reached.
+    #[default]
+    Batch,
+    /// Write at a fixed real-time rate, forever or for a set duration.
+    Live,
+}
+
+/// Where a job's lines are written. Every sink of a job gets every line.
+#[derive(Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Sink {
+    /// Rotating files in each service's directory under the output
+    /// directory, with their labels and manifest.
+    File,
+    /// Standard output of the process, with the lines of all services
+    /// interleaved.
+    Stdout,
+}
+
 #[derive(Clone)]
 pub struct GeneratorConfig {
     pub num_services: u32,
     pub target_bytes: u64,
     pub file_max_bytes: u64,
+    /// When active files are archived: at `file_max_bytes`, at period
+    /// boundaries, or both.
+    pub rotation: RotationPolicy,
+    /// How active files become archives: renamed, copied and truncated, or
+    /// shifted into numbered slots.
+    pub rotation_strategy: RotationStrategy,
+    /// Codec rotated archives are compressed with in the background.
+    pub compression: Compression,
+    /// Keep the newest archive uncompressed until the next rotation.
+    pub delay_compress: bool,
+    /// Limits on the archives each service keeps.
+    pub retention: RetentionPolicy,
+    /// Per-service retention overrides, keyed by service name.
+    pub service_retention: HashMap<String, RetentionPolicy>,
     pub output_dir: PathBuf,
+    /// Where lines are written.
+    pub sinks: Vec<Sink>,
     pub service_names: Vec<String>,
+    pub format: OutputFormat,
+    /// Per-service format overrides, keyed by service name.
+    pub service_formats: HashMap<String, OutputFormat>,
+    /// Relative weights of the log levels.
+    pub level_weights: LevelWeights,
+    /// Per-service level weight overrides, keyed by service name.
+    pub service_level_weights: HashMap<String, LevelWeights>,
+    /// Latency distributions of HTTP requests, database queries and external
+    /// calls.
+    pub latency: LatencyProfile,
+    /// Per-service latency overrides, keyed by service name.
+    pub service_latency: HashMap<String, LatencyProfile>,
+    /// Per-service profiles, keyed by service name, replacing the built-in
+    /// profile of that name (or the generic one).
+    pub service_profiles: HashMap<String, ServiceProfile>,
+    /// Relative volume weights, keyed by service name. Services without one
+    /// weigh 1, so by default every service writes the same share.
+    pub service_weights: HashMap<String, f64>,
+    /// Per-service file size limit overrides, keyed by service name.
+    pub service_file_max_bytes: HashMap<String, u64>,
+    /// Seed for reproducible output. When set, every service derives its RNG
+    /// from the seed and its index, and timestamps come from a simulated clock.
+    pub seed: Option<u64>,
+    /// Start of the simulated time window. Setting it switches timestamps
+    /// from the wall clock to a simulated clock (historical backfill).
+    pub start_time: Option<DateTime<Utc>>,
+    /// End of the simulated time window. Services stop when their clock
+    /// passes it, even if their byte target has not been reached.
+    pub end_time: Option<DateTime<Utc>>,
+    /// Event rate per service. Simulated in batch mode (without it, a
+    /// bounded window is spread evenly over each service's byte target);
+    /// real time in live mode.
+    pub events_per_second: Option<f64>,
+    pub mode: GenerationMode,
+    /// Live mode: real-time byte rate per service, instead of an event rate.
+    pub bytes_per_second: Option<u64>,
+    /// Live mode: stop after this long. Runs until stopped when unset.
+    pub duration_seconds: Option<u64>,
+    /// Shape of the event rate over simulated or live time.
+    pub traffic_profile: TrafficProfile,
+    /// Per-service traffic profile overrides, keyed by service name.
+    pub service_traffic_profiles: HashMap<String, TrafficProfile>,
+    /// Scheduled incidents, timed from the start of each service's clock.
+    pub incidents: Vec<Incident>,
+    /// Call graph for distributed traces spanning several services.
+    pub topology: Option<Topology>,
+    /// Share of regular events (0.0–1.0) that start a lifecycle pair, such as
+    /// "Request received" followed later by "Request completed successfully".
+    pub lifecycle_ratio: f64,
+    /// Job-wide users and tenants that lines are attributed to. Without it
+    /// every line gets a fresh random user id and IP.
+    pub entities: Option<EntityPoolConfig>,
+}
+
+impl GeneratorConfig {
+    /// Check the time window and live mode settings, which both the API and
+    /// the CLI accept.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, name) in self.service_names.iter().enumerate() {
+            validate_service_name(name)?;
+            if self.service_names[..i].contains(name) {
+                return Err(format!("Duplicate service name: {}", name));
+            }
+        }
+        if let Some((name, _)) = self.service_weights.iter().find(|(_, w)| !(**w > 0.0 && w.is_finite())) {
+            return Err(format!("Weight of service {} must be greater than 0", name));
+        }
+        // Volume and rate shares divide by the total
+        if !self.weights().iter().sum::<f64>().is_finite() {
+            return Err("Total weight of all services is too large".into());
+        }
+        if self.sinks.is_empty() {
+            return Err("sinks must name at least one of file, stdout".into());
+        }
+        if let Some((name, _)) = self.service_file_max_bytes.iter().find(|(_, max)| **max == 0) {
+            return Err(format!("File max size of service {} must be greater than 0", name));
+        }
+        for profile in std::iter::once(&self.traffic_profile).chain(self.service_traffic_profiles.values()) {
+            profile.validate()?;
+        }
+        for weights in std::iter::once(&self.level_weights).chain(self.service_level_weights.values()) {
+            weights.validate()?;
+        }
+        for latency in std::iter::once(&self.latency).chain(self.service_latency.values()) {
+            latency.validate()?;
+        }
+        for retention in std::iter::once(&self.retention).chain(self.service_retention.values()) {
+            retention.validate()?;
+        }
+        for (name, profile) in &self.service_profiles {
+            profile.validate().map_err(|e| format!("service_profiles.{}: {}", name, e))?;
+        }
+        for incident in &self.incidents {
+            incident.validate()?;
+            if let Some(unknown) = incident.services.iter().find(|name| !self.service_names.contains(name)) {
+                return Err(format!("Unknown service in incident {}: {}", incident.label(), unknown));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.lifecycle_ratio) {
+            return Err("lifecycle_ratio must be between 0 and 1".into());
+        }
+        if let Some(topology) = &self.topology {
+            topology.validate(&self.service_names)?;
+        }
+        if let Some(entities) = &self.entities {
+            entities.validate()?;
+        }
+        if self.mode == GenerationMode::Live {
+            if self.start_time.is_some() || self.end_time.is_some() {
+                return Err("Live mode uses real time and cannot be combined with start_time/end_time".into());
+            }
+            return match (self.events_per_second, self.bytes_per_second) {
+                (Some(eps), None) if eps > 0.0 && eps.is_finite() => Ok(()),
+                (None, Some(bps)) if bps > 0 => Ok(()),
+                (Some(_), Some(_)) => Err("Live mode takes either events_per_second or bytes_per_second, not both".into()),
+                _ => Err("Live mode requires events_per_second or bytes_per_second greater than 0".into()),
+            };
+        }
+        if self.bytes_per_second.is_some() || self.duration_seconds.is_some() {
+            return Err("bytes_per_second and duration_seconds are only valid in live mode".into());
+        }
+        if let Some(end) = self.end_time {
+            match self.start_time {
+                None => return Err("end_time requires start_time".into()),
+                Some(start) if end <= start => return Err("end_time must be after start_time".into()),
+                Some(_) => {}
+            }
+        }
+        if let Some(eps) = self.events_per_second {
+            if !(eps > 0.0 && eps.is_finite()) {
+                return Err("events_per_second must be greater than 0".into());
+            }
+            if self.start_time.is_none() && self.seed.is_none() {
+                return Err("events_per_second requires a simulated clock (start_time or seed)".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the default service list with an explicit one, moving each
+    /// entry's settings into the per-service maps.
+    pub fn set_services(&mut self, services: Vec<ServiceSpec>) {
+        self.num_services = services.len() as u32;
+        self.service_names = services.iter().map(|spec| spec.name.clone()).collect();
+        for spec in services {
+            let name = spec.name;
+            self.service_weights.insert(name.clone(), spec.weight);
+            if let Some(mb) = spec.file_max_size_mb {
+                self.service_file_max_bytes.insert(name.clone(), mb.saturating_mul(1024 * 1024));
+            }
+            if let Some(format) = spec.format {
+                self.service_formats.insert(name.clone(), format);
+            }
+            if let Some(weights) = spec.level_weights {
+                self.service_level_weights.insert(name.clone(), weights);
+            }
+            if let Some(latency) = spec.latency {
+                self.service_latency.insert(name.clone(), latency);
+            }
+            if let Some(profile) = spec.traffic_profile {
+                self.service_traffic_profiles.insert(name.clone(), profile);
+            }
+            if let Some(retention) = spec.retention {
+                self.service_retention.insert(name.clone(), retention);
+            }
+            if let Some(profile) = spec.profile {
+                self.service_profiles.insert(name, profile);
+            }
+        }
+    }
+
+    /// Volume weight of every service, in service order.
+    fn weights(&self) -> Vec<f64> {
+        self.service_names
+            .iter()
+            .map(|name| self.service_weights.get(name).copied().unwrap_or(1.0))
+            .collect()
+    }
+
+    /// Simulated clock of a service whose event rate is `rate_share` times the
+    /// configured per-service rate.
+    fn clock(&self, profile: &TrafficProfile, rate_share: f64) -> Clock {
+        if self.mode == GenerationMode::Live {
+            return Clock::Wall;
+        }
+        let mean_gap = self
+            .events_per_second
+            .map_or(clock::DEFAULT_MEAN_GAP_MICROS, |eps| 1_000_000.0 / (eps * rate_share));
+        match (self.start_time, self.seed) {
+            (Some(start), _) => Clock::simulated(start, self.end_time, mean_gap, profile.clone()),
+            (None, Some(_)) => Clock::simulated(clock::default_epoch(), None, mean_gap, profile.clone()),
+            (None, None) => Clock::Wall,
+        }
+    }
+}
+
+/// Rough size of one rendered line, used to pace a time window before a
+/// service has written anything.
+const ESTIMATED_LINE_BYTES: f64 = 300.0;
+
+/// How often (in clock ticks) a service re-spreads its remaining volume over the
+/// remaining window. Shaped profiles make this an integral, so not every line.
+const PACE_INTERVAL_EVENTS: u64 = 256;
+
+/// Longest a live-mode service sleeps before re-checking for cancellation.
+const MAX_LIVE_SLEEP: Duration = Duration::from_millis(100);
+
+/// Mixed into the job seed for the trace stream, so traces do not repeat the
+/// RNG stream of service 0.
+const TRACE_SEED_SALT: u64 = 0x7472_6163_6573_0000;
+
+/// Mixed into the job seed for the entity pool.
+const ENTITY_SEED_SALT: u64 = 0x656e_7469_7469_6573;
+
+/// Real-time rate limit for a live-mode service.
+#[derive(Clone, Copy)]
+enum LiveRate {
+    EventsPerSecond(f64),
+    BytesPerSecond(u64),
+}
+
+impl LiveRate {
+    fn scaled(self, share: f64) -> Self {
+        match self {
+            LiveRate::EventsPerSecond(eps) => LiveRate::EventsPerSecond(eps * share),
+            LiveRate::BytesPerSecond(bps) => LiveRate::BytesPerSecond(((bps as f64 * share) as u64).max(1)),
+        }
+    }
+}
+
+/// How far a live-mode service may fall behind its schedule (e.g. after a
+/// slow disk) before the missed slots are dropped instead of written in a burst.
+const MAX_LIVE_BACKLOG: Duration = Duration::from_secs(1);
+
+/// Keeps a live-mode service at its configured rate, shaped by its traffic
+/// profile at the current wall-clock time.
+struct Pacer {
+    rate: LiveRate,
+    profile: TrafficProfile,
+    /// When the next line is due, or `None` once the rate is so low that no
+    /// further line falls within any run.
+    next_due: Option<Instant>,
+    deadline: Option<Instant>,
+}
+
+impl Pacer {
+    fn new(rate: LiveRate, duration: Option<Duration>, profile: TrafficProfile) -> Self {
+        let started = Instant::now();
+        Self {
+            rate,
+            profile,
+            next_due: Some(started),
+            deadline: duration.map(|d| started + d),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Time left until the deadline, if there is one.
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// How long until the next line is due; zero when it may be written now.
+    fn ahead_by(&self) -> Duration {
+        self.next_due.map_or(Duration::MAX, |due| due.saturating_duration_since(Instant::now()))
+    }
+
+    /// Schedule the next line after one of `bytes` bytes was written.
+    fn record(&mut self, bytes: usize) {
+        let multiplier = self.profile.multiplier(Utc::now());
+        let gap = match self.rate {
+            LiveRate::EventsPerSecond(eps) => 1.0 / (eps * multiplier),
+            LiveRate::BytesPerSecond(bps) => bytes as f64 / (bps as f64 * multiplier),
+        };
+        let now = Instant::now();
+        let floor = now.checked_sub(MAX_LIVE_BACKLOG).unwrap_or(now);
+        self.next_due = self
+            .next_due
+            .zip(Duration::try_from_secs_f64(gap).ok())
+            .and_then(|(due, gap)| due.max(floor).checked_add(gap));
+    }
+}
+
+/// Service names that mimic real microservices. The first 30 get real-world
+/// names; the rest are named `microservice-N`.
+pub fn default_service_names(num_services: u32) -> Vec<String> {
+    const SERVICE_NAME_TEMPLATES: &[&str] = &[
+        "auth-service", "user-service", "order-service", "payment-service",
+        "inventory-service", "notification-service", "search-service",
+        "analytics-service", "gateway-service", "billing-service",
+        "shipping-service", "catalog-service", "review-service",
+        "recommendation-service", "email-service", "scheduler-service",
+        "config-service", "audit-service", "report-service", "cache-service",
+        "media-service", "webhook-service", "export-service", "import-service",
+        "monitoring-service", "logging-service", "discovery-service",
+        "rate-limiter-service", "session-service", "tenant-service",
+    ];
+
+    (0..num_services)
+        .map(|i| match SERVICE_NAME_TEMPLATES.get(i as usize) {
+            Some(name) => name.to_string(),
+            None => format!("microservice-{}", i + 1),
+        })
+        .collect()
+}
+
+/// One entry of an explicit service list: a name plus the settings that would
+/// otherwise come from the job-wide defaults.
+#[derive(Clone, Deserialize)]
+pub struct ServiceSpec {
+    pub name: String,
+    /// Share of the job's volume relative to the other services, e.g. 60 for
+    /// a gateway next to services of weight 1. In live mode and with a
+    /// simulated event rate, the service's rate is scaled the same way.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default)]
+    pub file_max_size_mb: Option<u64>,
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    #[serde(default)]
+    pub level_weights: Option<LevelWeights>,
+    #[serde(default)]
+    pub latency: Option<LatencyProfile>,
+    #[serde(default)]
+    pub traffic_profile: Option<TrafficProfile>,
+    #[serde(default)]
+    pub retention: Option<RetentionPolicy>,
+    /// Categories, messages and vocabulary, replacing the built-in profile.
+    #[serde(default)]
+    pub profile: Option<ServiceProfile>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Service names become directory names, so keep them to a portable set.
+fn validate_service_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 128
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid service name: {:?} (use letters, digits, '-', '_' and '.', not starting with '.')",
+            name
+        ))
+    }
+}
+
+/// Everything one service worker needs to produce its share of a job.
+struct ServiceJob {
+    name: String,
+    dir: PathBuf,
+    file_max_bytes: u64,
+    rotation: RotationPolicy,
+    rotation_strategy: RotationStrategy,
+    compression: Compression,
+    delay_compress: bool,
+    retention: RetentionPolicy,
+    sinks: Vec<Sink>,
+    /// Job event log shared by all services.
+    events: Arc<JobEventLog>,
+    target_bytes: u64,
+    format: OutputFormat,
+    rng: SmallRng,
+    clock: Clock,
+    /// Spread the byte target evenly over the clock's remaining window.
+    pace_to_window: bool,
+    /// Live mode only: real-time rate and optional duration.
+    live: Option<(LiveRate, Option<Duration>)>,
+    profile: TrafficProfile,
+    incidents: IncidentSchedule,
+    /// Spans of this service in the job's distributed traces.
+    traces: Option<TraceFeed>,
+    lifecycle_ratio: f64,
+    entities: Option<Arc<EntityPool>>,
+    level_weights: LevelWeights,
+    latency: LatencyProfile,
+    /// What the service logs about.
+    service_profile: ServiceProfile,
 }
 
 /// Start log generation. Spawns one blocking task per microservice.
@@ -56,24 +555,113 @@ pub fn start_generation(config: GeneratorConfig, state: Arc<GeneratorState>) {
     state.target_bytes.store(config.target_bytes, Ordering::SeqCst);
     state.running.store(true, Ordering::SeqCst);
     state.services_total.store(config.num_services as u64, Ordering::SeqCst);
+    let live = match (config.mode, config.bytes_per_second) {
+        (GenerationMode::Batch, _) => None,
+        (GenerationMode::Live, Some(bps)) => Some(LiveRate::BytesPerSecond(bps)),
+        (GenerationMode::Live, None) => Some(LiveRate::EventsPerSecond(config.events_per_second.unwrap_or(1.0))),
+    }
+    .map(|rate| (rate, config.duration_seconds.map(Duration::from_secs)));
+    state.live.store(live.is_some(), Ordering::SeqCst);
 
-    let bytes_per_service = config.target_bytes / config.num_services as u64;
+    // Split the volume by weight, and scale per-service rates so the mean
+    // service still runs at the configured rate
+    let weights = config.weights();
+    let total_weight: f64 = weights.iter().sum();
+    let mean_weight = total_weight / config.num_services as f64;
+    let mut bytes_assigned = 0;
+    // Every service replays the same traces, so they share one seed and start
+    let trace_seed = config.seed.map_or_else(rand::random, |seed| seed ^ TRACE_SEED_SALT);
+    let job_start = config.clock(&config.traffic_profile, 1.0).now();
+    let events = Arc::new(JobEventLog::new(&config.output_dir));
+    let entities = config.entities.as_ref().map(|entities| {
+        let seed = config.seed.map_or_else(rand::random, |seed| seed ^ ENTITY_SEED_SALT);
+        Arc::new(EntityPool::new(entities, seed))
+    });
 
     for i in 0..config.num_services {
         let svc_name = config.service_names[i as usize].clone();
-        let svc_dir = config.output_dir.join(&svc_name);
-        let file_max = config.file_max_bytes;
+        let format = config.service_formats.get(&svc_name).copied().unwrap_or(config.format);
+        let level_weights = config
+            .service_level_weights
+            .get(&svc_name)
+            .copied()
+            .unwrap_or(config.level_weights);
+        let latency = config.service_latency.get(&svc_name).copied().unwrap_or(config.latency);
+        let retention = config.service_retention.get(&svc_name).copied().unwrap_or(config.retention);
+        let file_max_bytes = config
+            .service_file_max_bytes
+            .get(&svc_name)
+            .copied()
+            .unwrap_or(config.file_max_bytes);
+        let service_profile = config
+            .service_profiles
+            .get(&svc_name)
+            .cloned()
+            .unwrap_or_else(|| ServiceProfile::builtin(&svc_name));
+        let profile = config
+            .service_traffic_profiles
+            .get(&svc_name)
+            .unwrap_or(&config.traffic_profile)
+            .clone();
+        let rng = match config.seed {
+            Some(seed) => SmallRng::seed_from_u64(seed.wrapping_add(i as u64)),
+            None => SmallRng::from_entropy(),
+        };
         let state = Arc::clone(&state);
-        let target = if i == config.num_services - 1 {
+        let rate_share = weights[i as usize] / mean_weight;
+        let target = if live.is_some() {
+            // Live services are bounded by time or cancellation, not volume
+            u64::MAX
+        } else if i == config.num_services - 1 {
             // Last service gets the remainder
-            config.target_bytes - bytes_per_service * (config.num_services as u64 - 1)
+            config.target_bytes - bytes_assigned
         } else {
-            bytes_per_service
+            let share = (config.target_bytes as f64 * weights[i as usize] / total_weight) as u64;
+            bytes_assigned += share;
+            share
+        };
+        let clock = config.clock(&profile, rate_share);
+        let incidents = IncidentSchedule::for_service(&config.incidents, &svc_name, &config.service_names, clock.now());
+        let traces = config
+            .topology
+            .as_ref()
+            .filter(|topology| topology.involves(&svc_name))
+            .map(|topology| {
+                let profile = config.traffic_profile.clone();
+                TraceFeed::new(topology.clone(), &svc_name, trace_seed, job_start, profile, entities.clone())
+            });
+        let job = ServiceJob {
+            dir: config.output_dir.join(&svc_name),
+            name: svc_name,
+            file_max_bytes,
+            rotation: config.rotation,
+            rotation_strategy: config.rotation_strategy,
+            compression: config.compression,
+            delay_compress: config.delay_compress,
+            retention,
+            sinks: config.sinks.clone(),
+            events: Arc::clone(&events),
+            target_bytes: target,
+            format,
+            rng,
+            clock,
+            pace_to_window: config.end_time.is_some() && config.events_per_second.is_none(),
+            live: live.map(|(rate, duration)| (rate.scaled(rate_share), duration)),
+            profile,
+            incidents,
+            traces,
+            lifecycle_ratio: config.lifecycle_ratio,
+            entities: entities.clone(),
+            level_weights,
+            latency,
+            service_profile,
         };
 
         task::spawn_blocking(move || {
-            if let Err(e) = generate_service_logs(&svc_name, &svc_dir, file_max, target, &state) {
+            let svc_name = job.name.clone();
+            if let Err(e) = generate_service_logs(job, &state) {
                 eprintln!("Error generating logs for {}: {}", svc_name, e);
+                state.services_failed.fetch_add(1, Ordering::SeqCst);
             }
             state.services_done.fetch_add(1, Ordering::SeqCst);
 
@@ -85,16 +673,123 @@ pub fn start_generation(config: GeneratorConfig, state: Arc<GeneratorState>) {
     }
 }
 
-fn generate_service_logs(
-    service_name: &str,
-    dir: &std::path::Path,
-    file_max_bytes: u64,
-    target_bytes: u64,
-    state: &GeneratorState,
-) -> std::io::Result<()> {
-    let mut writer = RotatingWriter::new(dir, file_max_bytes)?;
-    let mut rng = SmallRng::from_entropy();
-    let mut local_bytes: u64 = 0;
+/// Renders a service's events into its sinks and keeps the job's counters up
+/// to date.
+struct ServiceOutput<'a> {
+    /// Rotating files, unless the job has no file sink.
+    writer: Option<RotatingWriter>,
+    stdout: bool,
+    formatter: Box<dyn LogFormatter>,
+    line: String,
+    bytes: u64,
+    events: u64,
+    state: &'a GeneratorState,
+}
+
+impl ServiceOutput<'_> {
+    /// Write one event, labelling it with `anomaly` if given. Returns the
+    /// number of bytes written.
+    fn write(&mut self, event: &LogEvent, anomaly: Option<Anomaly>) -> std::io::Result<usize> {
+        self.line.clear();
+        self.formatter.format(event, &mut self.line);
+        let written = match &mut self.writer {
+            Some(writer) => {
+                let written = writer.write_line(&self.line, event.timestamp)?;
+                if let Some(anomaly) = anomaly {
+                    writer.label_last_line(event.timestamp, anomaly);
+                }
+                self.state.disk_bytes.fetch_add(written as u64, Ordering::Relaxed);
+                written
+            }
+            None => self.line.len() + 1,
+        };
+        if self.stdout {
+            // One lock per line, so lines of different services never mix
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(self.line.as_bytes())?;
+            stdout.write_all(b"\n")?;
+        }
+
+        self.bytes += written as u64;
+        self.events += 1;
+        self.state.bytes_written.fetch_add(written as u64, Ordering::Relaxed);
+        self.state.events_written.fetch_add(1, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(self, now: DateTime<Utc>) -> std::io::Result<()> {
+        match self.writer {
+            Some(writer) => writer.finish(now),
+            None => Ok(()),
+        }
+    }
+}
+
+fn generate_service_logs(job: ServiceJob, state: &GeneratorState) -> std::io::Result<()> {
+    let ServiceJob {
+        name,
+        dir,
+        file_max_bytes,
+        rotation,
+        rotation_strategy,
+        compression,
+        delay_compress,
+        retention,
+        sinks,
+        events,
+        target_bytes,
+        format,
+        mut rng,
+        mut clock,
+        pace_to_window,
+        live,
+        profile,
+        incidents,
+        mut traces,
+        lifecycle_ratio,
+        entities,
+        level_weights,
+        latency,
+        service_profile,
+    } = job;
+    let mut pacer = live.map(|(rate, duration)| Pacer::new(rate, duration, profile.clone()));
+    let service_name = name.as_str();
+    let ctx = ServiceContext {
+        name: service_name,
+        profile: &service_profile,
+        latency: &latency,
+        lifecycle_pairs: lifecycle_ratio > 0.0 && !format.is_access_log(),
+    };
+    let writer = if sinks.contains(&Sink::File) {
+        let compressor = (compression != Compression::None)
+            .then(|| Compressor::new(compression, delay_compress, Arc::clone(&state.disk_bytes)));
+        let retention = retention
+            .is_enabled()
+            .then(|| Retention::new(retention, service_name, events, Arc::clone(&state.disk_bytes)));
+        Some(RotatingWriter::new(&dir, file_max_bytes, rotation, rotation_strategy, compressor, retention, clock.now())?)
+    } else {
+        None
+    };
+    let hostname = format!("{}-{:08x}", service_name, rng.gen::<u32>());
+    let mut out = ServiceOutput {
+        writer,
+        stdout: sinks.contains(&Sink::Stdout),
+        formatter: format.formatter(&hostname, rng.gen_range(1000..65535)),
+        line: String::with_capacity(1024),
+        bytes: 0,
+        events: 0,
+        state,
+    };
+    let mut ticks: u64 = 0;
+    let mut queue = EventQueue::new();
+    let started = clock.now();
 
     loop {
         // Check cancellation
@@ -103,24 +798,109 @@ fn generate_service_logs(
         }
 
         // Check if this service has generated enough
-        if local_bytes >= target_bytes {
+        if out.bytes >= target_bytes {
             break;
         }
 
-        // Also check global target (in case of rounding)
-        if state.bytes_written.load(Ordering::Relaxed) >= state.target_bytes.load(Ordering::Relaxed) {
+        if let Some(pacer) = &pacer {
+            if pacer.expired() {
+                break;
+            }
+            let wait = pacer.ahead_by();
+            if !wait.is_zero() {
+                // Make everything written so far visible to tailing agents
+                out.flush()?;
+                std::thread::sleep(wait.min(MAX_LIVE_SLEEP));
+                continue;
+            }
+        }
+
+        if pace_to_window && ticks.is_multiple_of(PACE_INTERVAL_EVENTS) {
+            if let Some(remaining_micros) = clock.remaining_weighted_micros() {
+                let (avg_line, lines_per_tick) = if ticks > 0 {
+                    (out.bytes as f64 / out.events as f64, out.events as f64 / ticks as f64)
+                } else {
+                    (ESTIMATED_LINE_BYTES, 1.0)
+                };
+                // Trace spans are written between ticks, so fewer ticks are needed
+                let ticks_left = ((target_bytes - out.bytes) as f64 / avg_line / lines_per_tick).max(1.0);
+                clock.set_mean_gap_micros(remaining_micros / ticks_left);
+            }
+        }
+
+        let Some(timestamp) = clock.tick(&mut rng) else {
+            // Simulated clock passed the end of the time window
             break;
+        };
+        ticks += 1;
+
+        // Queued lines (trace spans, lifecycle end lines) stamped before this
+        // event come first, keeping the file in time order
+        if let Some(traces) = &mut traces {
+            traces.schedule_until(&mut rng, &ctx, timestamp, format.is_access_log(), &mut queue);
+        }
+        while let Some(event) = queue.pop_due(timestamp) {
+            // Lines written during an incident are anomalous whatever produced them
+            let anomaly = incidents.active(event.timestamp).map(|incident| incident.anomaly());
+            let written = out.write(&event, anomaly)?;
+            if let Some(pacer) = &mut pacer {
+                pacer.record(written);
+            }
         }
 
-        let level = LogLevel::random(&mut rng);
-        let msg = messages::generate_message(&mut rng, level, service_name);
-        let written = writer.write_line(&msg)?;
+        let incident = incidents.active(timestamp);
+        let user = entities.as_ref().map(|pool| pool.sample(&mut rng));
+        let identify = |event: &mut LogEvent| {
+            if let (Some(pool), Some(user)) = (&entities, user) {
+                pool.apply(user, event);
+            }
+        };
+        let mut event = match &incident {
+            Some(incident) => {
+                let level = incident.random_level(&mut rng);
+                incident.generate_event(&mut rng, level, &ctx, timestamp, format.is_access_log())
+            }
+            None if lifecycle_ratio > 0.0 && !format.is_access_log() && rng.gen_bool(lifecycle_ratio) => {
+                // End the pair before the run does, so its end line is written
+                let max_duration = match (clock.end(), &pacer) {
+                    (Some(end), _) => Some(end - timestamp),
+                    (None, Some(pacer)) => pacer.remaining().map(|left| TimeDelta::from_std(left).unwrap_or(TimeDelta::MAX)),
+                    (None, None) => {
+                        // Batch: extrapolate from the share of the volume written so far
+                        let left = target_bytes.saturating_sub(out.bytes) as f64 / out.bytes.max(1) as f64;
+                        let elapsed = (timestamp - started).num_microseconds().unwrap_or(i64::MAX) as f64;
+                        Some(TimeDelta::microseconds((elapsed * left) as i64))
+                    }
+                };
+                // A clock far in the future may have little representable time left
+                let max_duration = max_duration.map(|max| max.min(DateTime::<Utc>::MAX_UTC - timestamp));
+                let (start, mut end) = messages::generate_lifecycle_pair(&mut rng, &ctx, timestamp, max_duration);
+                identify(&mut end);
+                queue.push(end);
+                start
+            }
+            None => {
+                let level = LogLevel::weighted(&mut rng, &level_weights);
+                if format.is_access_log() {
+                    messages::generate_access_event(&mut rng, level, &ctx, timestamp)
+                } else {
+                    messages::generate_event(&mut rng, level, &ctx, timestamp)
+                }
+            }
+        };
+        identify(&mut event);
+        let written = out.write(&event, incident.map(|incident| incident.anomaly()))?;
+        if let Some(pacer) = &mut pacer {
+            pacer.record(written);
+        }
+    }
 
-        local_bytes += written as u64;
-        state.bytes_written.fetch_add(written as u64, Ordering::Relaxed);
+    // Write the lines still queued, so no lifecycle pair or trace is left
+    // without its end
+    while let Some(event) = queue.pop() {
+        let anomaly = incidents.active(event.timestamp).map(|incident| incident.anomaly());
+        out.write(&event, anomaly)?;
     }
 
-    writer.flush()?;
-    Ok(())
+    out.finish(clock.now())
 }
-
@@ -0,0 +1,390 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::labels::Anomaly;
+use crate::messages::{self, FieldValue, LevelWeights, LogEvent, LogLevel, ServiceContext};
+
+/// The kind of failure an incident simulates. Each kind has a fixed effect on
+/// the level mix, messages, latencies and dependency names.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentKind {
+    /// The affected services cannot reach their upstreams and fail requests.
+    Outage,
+    /// The database slows down: slow queries, pool exhaustion, timeouts.
+    LatencySpike,
+    /// An upstream certificate expires and TLS handshakes start failing.
+    CertificateExpiry,
+    /// A generic burst of errors with otherwise normal messages.
+    ErrorStorm,
+}
+
+/// An incident scheduled on a job, relative to the start of the job's clock.
+#[derive(Clone, Deserialize)]
+pub struct Incident {
+    pub kind: IncidentKind,
+    /// Label used to identify the incident; defaults to the kind.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Affected services. Empty means every service.
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Seconds after the job start when the incident begins.
+    pub start_offset_seconds: u64,
+    pub duration_seconds: u64,
+    /// Delay between consecutive affected services joining the incident, to
+    /// model a cascade. Defaults to 60 s for certificate expiry, 0 otherwise.
+    #[serde(default)]
+    pub stagger_seconds: Option<u64>,
+    /// External dependency named in the affected lines, e.g. `"Redis Cluster"`.
+    #[serde(default)]
+    pub dependency: Option<String>,
+}
+
+/// Upper bound on incident offsets, durations and staggers (100 years), which
+/// keeps every window within the range of representable timestamps.
+const MAX_INCIDENT_SECONDS: u64 = 100 * 365 * 24 * 3600;
+
+impl Incident {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.duration_seconds == 0 {
+            return Err(format!("Incident {} must have a duration greater than 0", self.label()));
+        }
+        let seconds = [Some(self.start_offset_seconds), Some(self.duration_seconds), self.stagger_seconds];
+        if seconds.into_iter().flatten().any(|s| s > MAX_INCIDENT_SECONDS) {
+            return Err(format!(
+                "Incident {}: start_offset_seconds, duration_seconds and stagger_seconds must be at most {}",
+                self.label(),
+                MAX_INCIDENT_SECONDS
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn label(&self) -> &str {
+        match &self.name {
+            Some(name) => name,
+            None => self.kind.as_str(),
+        }
+    }
+}
+
+impl IncidentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentKind::Outage => "outage",
+            IncidentKind::LatencySpike => "latency_spike",
+            IncidentKind::CertificateExpiry => "certificate_expiry",
+            IncidentKind::ErrorStorm => "error_storm",
+        }
+    }
+
+    fn effect(&self) -> &'static IncidentEffect {
+        match self {
+            IncidentKind::Outage => &OUTAGE,
+            IncidentKind::LatencySpike => &LATENCY_SPIKE,
+            IncidentKind::CertificateExpiry => &CERTIFICATE_EXPIRY,
+            IncidentKind::ErrorStorm => &ERROR_STORM,
+        }
+    }
+
+    fn default_stagger_seconds(&self) -> u64 {
+        match self {
+            IncidentKind::CertificateExpiry => 60,
+            _ => 0,
+        }
+    }
+}
+
+const fn weights(info: u32, warn: u32, error: u32, fatal: u32) -> LevelWeights {
+    LevelWeights {
+        trace: 0,
+        debug: 0,
+        info,
+        warn,
+        error,
+        fatal,
+    }
+}
+
+/// How an incident changes the lines of an affected service.
+struct IncidentEffect {
+    /// Level weights while the incident is active.
+    level_weights: LevelWeights,
+    /// WARN/ERROR messages and the detail categories they come with. Empty
+    /// keeps the normal message mix.
+    warn: &'static [Symptom],
+    error: &'static [Symptom],
+    error_types: &'static [&'static str],
+    error_statuses: &'static [u16],
+    latency_factor: f64,
+    dependency: Option<&'static str>,
+}
+
+/// Messages that belong to one detail category, e.g. slow queries to `db`.
+struct Symptom {
+    category: &'static str,
+    templates: &'static [&'static str],
+}
+
+static OUTAGE: IncidentEffect = IncidentEffect {
+    // A few instances crash outright
+    level_weights: weights(200, 60, 240, 2),
+    warn: &[
+        Symptom {
+            category: "external",
+            templates: &[
+                "External service degraded performance",
+                "Retry attempt required",
+                "Circuit breaker status: HALF-OPEN",
+            ],
+        },
+        Symptom { category: "http", templates: &["Response time exceeded SLA threshold"] },
+    ],
+    error: &[
+        Symptom {
+            category: "external",
+            templates: &[
+                "Connection refused by upstream",
+                "Circuit breaker tripped: OPEN",
+                "All retry attempts exhausted",
+            ],
+        },
+        Symptom {
+            category: "http",
+            templates: &[
+                "Request processing failed",
+                "Critical: health check failed",
+                "Cascade failure detected across services",
+            ],
+        },
+    ],
+    error_types: &[
+        "ServiceUnavailableException", "ConnectionTimeoutException",
+        "CircuitBreakerOpenException", "RetryExhaustedException", "SocketException",
+    ],
+    error_statuses: &[502, 503, 504],
+    latency_factor: 5.0,
+    dependency: None,
+};
+
+static LATENCY_SPIKE: IncidentEffect = IncidentEffect {
+    level_weights: weights(400, 80, 15, 0),
+    warn: &[
+        Symptom { category: "db", templates: &["Slow query detected"] },
+        Symptom {
+            category: "pool",
+            templates: &["Connection pool near capacity", "Thread pool saturation warning"],
+        },
+        Symptom { category: "http", templates: &["Response time exceeded SLA threshold"] },
+    ],
+    error: &[
+        Symptom {
+            category: "db",
+            templates: &[
+                "Database connection lost",
+                "Deadlock detected in transaction",
+                "Timeout waiting for distributed lock",
+            ],
+        },
+        Symptom { category: "http", templates: &["Request processing failed"] },
+    ],
+    error_types: &[
+        "DatabaseConnectionException", "DeadlockDetectedException",
+        "LockAcquisitionException", "GatewayTimeoutException",
+    ],
+    error_statuses: &[503, 504],
+    latency_factor: 12.0,
+    dependency: Some("PostgreSQL Primary"),
+};
+
+static CERTIFICATE_EXPIRY: IncidentEffect = IncidentEffect {
+    level_weights: weights(300, 60, 150, 0),
+    warn: &[Symptom {
+        category: "external",
+        templates: &["Certificate expiring soon", "Retry attempt required"],
+    }],
+    error: &[
+        Symptom {
+            category: "external",
+            templates: &[
+                "SSL handshake failed",
+                "External service call failed",
+                "All retry attempts exhausted",
+            ],
+        },
+        Symptom { category: "http", templates: &["Request processing failed"] },
+    ],
+    error_types: &["CertificateExpiredException", "SSLHandshakeException"],
+    error_statuses: &[502, 503],
+    latency_factor: 2.0,
+    dependency: Some("Auth0"),
+};
+
+static ERROR_STORM: IncidentEffect = IncidentEffect {
+    level_weights: weights(300, 50, 200, 0),
+    warn: &[],
+    error: &[],
+    error_types: &[],
+    error_statuses: &[],
+    latency_factor: 1.5,
+    dependency: None,
+};
+
+/// Detail fields holding a duration in milliseconds, scaled by the latency factor.
+const LATENCY_FIELDS: &[&str] = &[
+    "latency_ms", "query_time_ms", "last_attempt_ms", "statement_timeout_ms", "max_wait_ms",
+];
+
+/// An incident window as seen by one service.
+struct Window {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    label: String,
+    kind: IncidentKind,
+    effect: &'static IncidentEffect,
+    dependency: Option<String>,
+}
+
+/// An incident affecting a service at a given moment.
+pub struct ActiveIncident<'a> {
+    label: &'a str,
+    kind: IncidentKind,
+    effect: &'static IncidentEffect,
+    dependency: Option<&'a str>,
+}
+
+/// The incident windows that apply to one service.
+pub struct IncidentSchedule {
+    windows: Vec<Window>,
+}
+
+impl IncidentSchedule {
+    /// Resolve the job's incidents for `service`, with offsets counted from
+    /// `job_start`.
+    pub fn for_service(
+        incidents: &[Incident],
+        service: &str,
+        all_services: &[String],
+        job_start: DateTime<Utc>,
+    ) -> Self {
+        let windows = incidents
+            .iter()
+            .filter_map(|incident| {
+                // Position of this service in the incident's cascade order
+                let position = if incident.services.is_empty() {
+                    all_services.iter().position(|s| s == service)?
+                } else {
+                    incident.services.iter().position(|s| s == service)?
+                };
+                let stagger = incident
+                    .stagger_seconds
+                    .unwrap_or_else(|| incident.kind.default_stagger_seconds());
+                // Times past the end of the representable range are never reached
+                let after = |seconds: Option<u64>| {
+                    let delta = Duration::try_seconds(i64::try_from(seconds?).ok()?)?;
+                    job_start.checked_add_signed(delta)
+                };
+                let start = after(
+                    stagger
+                        .checked_mul(position as u64)
+                        .and_then(|delay| delay.checked_add(incident.start_offset_seconds)),
+                )?;
+                let end = after(incident.start_offset_seconds.checked_add(incident.duration_seconds))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                (start < end).then(|| Window {
+                    start,
+                    end,
+                    label: incident.label().to_string(),
+                    kind: incident.kind,
+                    effect: incident.kind.effect(),
+                    dependency: incident.dependency.clone(),
+                })
+            })
+            .collect();
+        Self { windows }
+    }
+
+    /// The first incident active at `t`, if any.
+    pub fn active(&self, t: DateTime<Utc>) -> Option<ActiveIncident<'_>> {
+        self.windows
+            .iter()
+            .find(|w| w.start <= t && t < w.end)
+            .map(|w| ActiveIncident {
+                label: &w.label,
+                kind: w.kind,
+                effect: w.effect,
+                dependency: w.dependency.as_deref(),
+            })
+    }
+}
+
+impl ActiveIncident<'_> {
+    /// Ground-truth label for lines written during the incident.
+    pub fn anomaly(&self) -> Anomaly {
+        Anomaly {
+            anomaly: "incident",
+            kind: self.kind.as_str(),
+            name: self.label.to_string(),
+        }
+    }
+
+    pub fn random_level(&self, rng: &mut impl Rng) -> LogLevel {
+        LogLevel::weighted(rng, &self.effect.level_weights)
+    }
+
+    /// Generate an event of `level` that reads like part of the incident.
+    pub fn generate_event<'a>(
+        &self,
+        rng: &mut impl Rng,
+        level: LogLevel,
+        ctx: &ServiceContext<'a>,
+        timestamp: DateTime<Utc>,
+        access_log: bool,
+    ) -> LogEvent<'a> {
+        let symptoms = match level {
+            LogLevel::Warn => self.effect.warn,
+            LogLevel::Error => self.effect.error,
+            _ => &[],
+        };
+        let mut event = if access_log {
+            messages::generate_access_event(rng, level, ctx, timestamp)
+        } else if symptoms.is_empty() {
+            messages::generate_event(rng, level, ctx, timestamp)
+        } else {
+            let symptom = &symptoms[rng.gen_range(0..symptoms.len())];
+            let template = symptom.templates[rng.gen_range(0..symptom.templates.len())];
+            messages::generate_event_as(rng, level, ctx, timestamp, symptom.category, template)
+        };
+        self.rewrite_fields(rng, &mut event);
+        event
+    }
+
+    /// Point latencies, dependency names, error types and status codes at
+    /// the incident.
+    fn rewrite_fields(&self, rng: &mut impl Rng, event: &mut LogEvent) {
+        let effect = self.effect;
+        let dependency = self.dependency.or(effect.dependency);
+        let is_error = matches!(event.level, LogLevel::Error | LogLevel::Fatal);
+        for (key, value) in event.fields.iter_mut() {
+            match (*key, &mut *value) {
+                (k, FieldValue::Int(ms)) if LATENCY_FIELDS.contains(&k) => {
+                    *ms = (*ms as f64 * effect.latency_factor) as i64;
+                }
+                ("external_service", FieldValue::Str(name)) => {
+                    if let Some(dependency) = dependency {
+                        *name = dependency.to_string();
+                    }
+                }
+                ("error_type", FieldValue::Str(name)) if !effect.error_types.is_empty() => {
+                    *name = effect.error_types[rng.gen_range(0..effect.error_types.len())].to_string();
+                }
+                ("status", FieldValue::Int(status)) if is_error && !effect.error_statuses.is_empty() => {
+                    *status = effect.error_statuses[rng.gen_range(0..effect.error_statuses.len())] as i64;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Name of the ground-truth sidecar in each service directory.
+pub const LABELS_FILE_NAME: &str = "labels.jsonl";
+
+/// Why a generated line is interesting to a detector.
+#[derive(Clone, Serialize)]
+pub struct Anomaly {
+    /// Broad class of the anomaly, e.g. `incident`.
+    pub anomaly: &'static str,
+    /// Specific kind within the class, e.g. `outage`.
+    pub kind: &'static str,
+    /// Label of the injected event, e.g. the incident name.
+    pub name: String,
+}
+
+/// A labelled line whose file may still be renamed by rotation.
+struct PendingLabel {
+    offset: u64,
+    line: u64,
+    timestamp: DateTime<Utc>,
+    anomaly: Anomaly,
+}
+
+/// A closed file with labels, tracked while numbered rotation may still
+/// rename it.
+struct LabelledFile {
+    file: String,
+    /// Name until compression finishes, like `file` before its suffix.
+    plain_file: String,
+}
+
+#[derive(Serialize)]
+struct LabelEntry<'a> {
+    file: &'a str,
+    offset: u64,
+    line: u64,
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    anomaly: &'a Anomaly,
+}
+
+/// Sidecar line recording that a labelled file was renamed or deleted.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum FileEvent<'a> {
+    Renamed { renamed: &'a str, to: &'a str },
+    Deleted { deleted: &'a str },
+}
+
+/// The fields of a sidecar line that say which file it is about.
+#[derive(Deserialize)]
+struct SidecarLine {
+    file: Option<String>,
+    renamed: Option<String>,
+    to: Option<String>,
+    deleted: Option<String>,
+}
+
+/// JSONL sidecar recording where labelled lines ended up.
+///
+/// Labels are held until the file they belong to is closed, so entries name
+/// the file's final (archived) name. When archives keep being renamed after
+/// that (numbered rotation), the sidecar stays append-only while the service
+/// runs: renames and deletions of labelled files are appended as
+/// `{"renamed": .., "to": ..}` and `{"deleted": ..}` lines, and the sidecar
+/// is rewritten once at the end with every label under its file's final name.
+/// The sidecar is only created once the first label is written.
+pub struct LabelSidecar {
+    dir: PathBuf,
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    pending: Vec<PendingLabel>,
+    /// Closed files with labels that are still on disk, oldest first, if
+    /// they may be renamed later.
+    renamable: Option<Vec<LabelledFile>>,
+    /// Whether the sidecar holds rename or delete lines.
+    has_events: bool,
+}
+
+impl LabelSidecar {
+    /// A sidecar for `dir`. With `follow_renames`, `renamed` records renames
+    /// of labelled files.
+    pub fn new(dir: &Path, follow_renames: bool) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            path: dir.join(LABELS_FILE_NAME),
+            writer: None,
+            pending: Vec::new(),
+            renamable: follow_renames.then(Vec::new),
+            has_events: false,
+        }
+    }
+
+    /// Label the line starting at byte `offset` (1-based `line`) of the
+    /// current file.
+    pub fn push(&mut self, offset: u64, line: u64, timestamp: DateTime<Utc>, anomaly: Anomaly) {
+        self.pending.push(PendingLabel { offset, line, timestamp, anomaly });
+    }
+
+    /// Write out the labels of the current file, which is now at
+    /// `plain_file_name` and finally named `file_name`.
+    pub fn commit(&mut self, file_name: &str, plain_file_name: &str) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if self.writer.is_none() {
+            self.writer = Some(BufWriter::new(File::create(&self.path)?));
+        }
+        let writer = self.writer.as_mut().unwrap();
+        for label in self.pending.drain(..) {
+            let entry = LabelEntry {
+                file: file_name,
+                offset: label.offset,
+                line: label.line,
+                timestamp: label.timestamp,
+                anomaly: &label.anomaly,
+            };
+            write_line(writer, &entry)?;
+        }
+        writer.flush()?;
+        if let Some(renamable) = &mut self.renamable {
+            renamable.push(LabelledFile {
+                file: file_name.to_string(),
+                plain_file: plain_file_name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Follow files renamed on disk, like `Manifest::renamed`, appending a
+    /// line for each labelled file. Files whose new name does not exist were
+    /// deleted before the rename and are recorded as deleted instead.
+    pub fn renamed(&mut self, rename: impl Fn(&Path) -> Option<PathBuf>) -> io::Result<()> {
+        let rename = |name: &str| rename(Path::new(name)).map(|path| path.to_string_lossy().into_owned());
+        self.record(|dir, entry| {
+            let (Some(file), Some(plain_file)) = (rename(&entry.file), rename(&entry.plain_file)) else {
+                return Some(entry);
+            };
+            (dir.join(&file).exists() || dir.join(&plain_file).exists()).then_some(LabelledFile { file, plain_file })
+        })
+    }
+
+    /// Record labelled files deleted since the last rename and, if the
+    /// sidecar holds renames, rewrite it with every label under its file's
+    /// final name, leaving out the labels of deleted files.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.record(|dir, entry| {
+            (dir.join(&entry.file).exists() || dir.join(&entry.plain_file).exists()).then_some(entry)
+        })?;
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+        writer.flush()?;
+        drop(writer);
+        if !self.has_events {
+            return Ok(());
+        }
+
+        // The first pass finds each file's final name, the second rewrites
+        // its labels
+        let final_names = replay(&self.path, |_, _, _| Ok(()))?;
+        let partial = self.dir.join(format!("{}.tmp", LABELS_FILE_NAME));
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        replay(&self.path, |index, file, line| {
+            let Some(final_name) = &final_names[index] else {
+                return Ok(());
+            };
+            let rest = line
+                .strip_prefix(&format!("{{\"file\":{}", serde_json::to_string(file)?))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unexpected label line"))?;
+            writeln!(writer, "{{\"file\":{}{}", serde_json::to_string(final_name)?, rest)
+        })?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&partial, &self.path)
+    }
+
+    /// Pass every renamable file through `update`, which returns it under
+    /// its current name, or `None` once it is gone, and append a line for
+    /// each change.
+    fn record(&mut self, mut update: impl FnMut(&Path, LabelledFile) -> Option<LabelledFile>) -> io::Result<()> {
+        let (Some(renamable), Some(writer)) = (&mut self.renamable, &mut self.writer) else {
+            return Ok(());
+        };
+        // Oldest first, which is highest number first, so a file is never
+        // renamed to a name another file still has
+        for entry in std::mem::take(renamable) {
+            let name = entry.file.clone();
+            match update(&self.dir, entry) {
+                Some(entry) if entry.file == name => renamable.push(entry),
+                Some(entry) => {
+                    write_line(writer, &FileEvent::Renamed { renamed: &name, to: &entry.file })?;
+                    renamable.push(entry);
+                    self.has_events = true;
+                }
+                None => {
+                    write_line(writer, &FileEvent::Deleted { deleted: &name })?;
+                    self.has_events = true;
+                }
+            }
+        }
+        writer.flush()
+    }
+}
+
+fn write_line(writer: &mut impl Write, value: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")
+}
+
+/// Read the sidecar at `path`, following every file through the rename and
+/// delete lines. Calls `label` with the file's index in order of first
+/// appearance, its name at the time and the line, for every label line.
+/// Returns each file's final name, or `None` if it was deleted.
+fn replay(
+    path: &Path,
+    mut label: impl FnMut(usize, &str, &str) -> io::Result<()>,
+) -> io::Result<Vec<Option<String>>> {
+    let mut names: Vec<Option<String>> = Vec::new();
+    let mut current: HashMap<String, usize> = HashMap::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        match serde_json::from_str(&line)? {
+            SidecarLine { file: Some(file), .. } => {
+                let index = *current.entry(file.clone()).or_insert_with(|| {
+                    names.push(Some(file.clone()));
+                    names.len() - 1
+                });
+                label(index, &file, &line)?;
+            }
+            SidecarLine { renamed: Some(from), to: Some(to), .. } => {
+                if let Some(index) = current.remove(&from) {
+                    names[index] = Some(to.clone());
+                    current.insert(to, index);
+                }
+            }
+            SidecarLine { deleted: Some(name), .. } => {
+                if let Some(index) = current.remove(&name) {
+                    names[index] = None;
+                }
+            }
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected sidecar line")),
+        }
+    }
+    Ok(names)
+}
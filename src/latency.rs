@@ -0,0 +1,145 @@
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::Deserialize;
+
+use crate::messages::{FieldValue, Fields};
+
+/// z-score of the 99th percentile of a standard normal distribution.
+const P99_Z: f64 = 2.326_347_874;
+
+/// Longest latency a model reports, so extreme tails stay readable.
+const MAX_LATENCY_MS: f64 = 600_000.0;
+
+/// Log-normal latency distribution of one kind of operation, described by its
+/// median and 99th percentile.
+#[derive(Clone, Copy, Deserialize)]
+pub struct LatencyModel {
+    pub median_ms: f64,
+    pub p99_ms: f64,
+    /// Operations slower than this are logged as slow warnings. Defaults to
+    /// the p99.
+    #[serde(default)]
+    pub slow_ms: Option<f64>,
+}
+
+impl LatencyModel {
+    const fn new(median_ms: f64, p99_ms: f64, slow_ms: f64) -> Self {
+        Self {
+            median_ms,
+            p99_ms,
+            slow_ms: Some(slow_ms),
+        }
+    }
+
+    fn validate(&self, category: &str) -> Result<(), String> {
+        let positive = |v: f64| v > 0.0 && v.is_finite();
+        if !positive(self.median_ms) || !positive(self.p99_ms) || self.p99_ms <= self.median_ms {
+            return Err(format!("latency.{}: p99_ms must be greater than median_ms, and both greater than 0", category));
+        }
+        if !self.slow_ms.is_none_or(positive) {
+            return Err(format!("latency.{}.slow_ms must be greater than 0", category));
+        }
+        Ok(())
+    }
+
+    pub fn threshold_ms(&self) -> f64 {
+        self.slow_ms.unwrap_or(self.p99_ms)
+    }
+
+    fn mu(&self) -> f64 {
+        self.median_ms.ln()
+    }
+
+    fn sigma(&self) -> f64 {
+        (self.p99_ms / self.median_ms).ln() / P99_Z
+    }
+
+    /// Draw a latency in whole milliseconds.
+    pub fn sample(&self, rng: &mut impl Rng) -> u32 {
+        let z: f64 = rng.sample(StandardNormal);
+        (self.mu() + self.sigma() * z).exp().round().clamp(1.0, MAX_LATENCY_MS) as u32
+    }
+
+    /// Draw a latency from the part of the distribution above the slow
+    /// threshold, for lines that report a slow or failed operation.
+    pub fn sample_slow(&self, rng: &mut impl Rng) -> u32 {
+        let threshold = self.threshold_ms();
+        let z0 = (threshold.ln() - self.mu()) / self.sigma();
+        let z = if z0 <= 0.0 {
+            // At least half of all draws land above the threshold
+            loop {
+                let z: f64 = rng.sample(StandardNormal);
+                if z > z0 {
+                    break z;
+                }
+            }
+        } else {
+            // Robert (1995): exponential proposals for a normal tail
+            let alpha = (z0 + (z0 * z0 + 4.0).sqrt()) / 2.0;
+            loop {
+                let z = z0 - rng.gen_range(f64::EPSILON..1.0).ln() / alpha;
+                if rng.gen::<f64>() <= (-(z - alpha).powi(2) / 2.0).exp() {
+                    break z;
+                }
+            }
+        };
+        let ms = (self.mu() + self.sigma() * z).exp().ceil();
+        ms.clamp(threshold.floor() + 1.0, MAX_LATENCY_MS.max(threshold + 1.0)) as u32
+    }
+}
+
+/// Latency models of a service's HTTP requests, database queries and
+/// external calls.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LatencyProfile {
+    pub http: LatencyModel,
+    pub db: LatencyModel,
+    pub external: LatencyModel,
+}
+
+impl Default for LatencyProfile {
+    fn default() -> Self {
+        Self {
+            http: LatencyModel::new(45.0, 800.0, 1000.0),
+            db: LatencyModel::new(8.0, 250.0, 500.0),
+            external: LatencyModel::new(120.0, 1500.0, 2000.0),
+        }
+    }
+}
+
+/// Warning an INFO line turns into when its latency crosses the slow
+/// threshold: (category, latency field, warning template).
+const SLOW_WARNINGS: &[(&str, &str, &str)] = &[
+    ("http", "latency_ms", "Response time exceeded SLA threshold"),
+    ("db", "query_time_ms", "Slow query detected"),
+    ("external", "latency_ms", "External service degraded performance"),
+];
+
+impl LatencyProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        self.http.validate("http")?;
+        self.db.validate("db")?;
+        self.external.validate("external")
+    }
+
+    fn model(&self, category: &str) -> Option<&LatencyModel> {
+        match category {
+            "http" => Some(&self.http),
+            "db" => Some(&self.db),
+            "external" => Some(&self.external),
+            _ => None,
+        }
+    }
+
+    /// The slow-warning template for a line of `category` whose latency
+    /// field is above the category's threshold, if it is.
+    pub fn slow_warning(&self, category: &str, fields: &Fields) -> Option<&'static str> {
+        let model = self.model(category)?;
+        let &(_, field, template) = SLOW_WARNINGS.iter().find(|(c, _, _)| *c == category)?;
+        fields
+            .iter()
+            .any(|(key, value)| *key == field && matches!(value, FieldValue::Int(ms) if *ms as f64 > model.threshold_ms()))
+            .then_some(template)
+    }
+}
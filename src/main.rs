@@ -1,21 +1,62 @@
+mod cli;
+mod clock;
+mod compression;
+mod entities;
+mod events;
+mod format;
 mod generator;
+mod incidents;
+mod labels;
+mod latency;
+mod manifest;
 mod messages;
+mod profiles;
+mod retention;
 mod rotation;
+mod scenario;
 mod server;
+mod traces;
+mod traffic;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
+use clap::Parser;
+
+use cli::{Cli, Command, ServeArgs};
 use generator::GeneratorState;
+use server::AppState;
 
 #[tokio::main]
 async fn main() {
-    let state = Arc::new(GeneratorState::new());
+    let cli = Cli::parse();
+    let command = cli
+        .command
+        .unwrap_or_else(|| Command::Serve(ServeArgs::parse_from(["log-generator"])));
+    match command {
+        Command::Serve(args) => serve(args).await,
+        Command::Generate(args) => std::process::exit(cli::run_generate(*args).await),
+        Command::Run(args) => std::process::exit(cli::run_scenario(args).await),
+    }
+}
+
+async fn serve(args: ServeArgs) {
+    let state = AppState {
+        generator: Arc::new(GeneratorState::new()),
+        output_root: Arc::new(args.output_dir),
+        scenarios: Arc::default(),
+    };
     let app = server::create_router(state);
 
-    let addr = "0.0.0.0:3000";
+    let addr = SocketAddr::new(args.bind, args.port);
+    let url = if args.bind.is_unspecified() {
+        format!("http://localhost:{}", args.port)
+    } else {
+        format!("http://{}", addr)
+    };
     println!("╔══════════════════════════════════════════╗");
     println!("║       Log Generator is running!          ║");
-    println!("║  Open http://localhost:3000 in browser    ║");
+    println!("║{:<42}║", format!("  Open {} in browser", url));
     println!("╚══════════════════════════════════════════╝");
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
@@ -0,0 +1,123 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Name of the file manifest in each service directory.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// One closed file of a service, as written.
+#[derive(Serialize)]
+struct ManifestEntry {
+    /// Name in the service directory, once any compression has finished.
+    file: String,
+    /// Name until then.
+    #[serde(skip)]
+    plain_file: String,
+    /// Earliest and latest line timestamps in the file.
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    lines: u64,
+    /// Size and SHA-256 of the uncompressed content.
+    bytes: u64,
+    sha256: String,
+    /// Removed by retention since it was written.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    deleted: bool,
+}
+
+#[derive(Serialize)]
+struct ManifestDocument<'a> {
+    /// Whether the service finished; until then the active file is left out.
+    complete: bool,
+    files: &'a [ManifestEntry],
+}
+
+/// JSON manifest of every file a service wrote, oldest first, so a consumer
+/// can check that each line and byte arrived.
+///
+/// Lines are hashed as they are written, so closing a file never reads it
+/// back. The manifest is rewritten on every rotation and when the service
+/// finishes, each time under a temporary name first so readers never see a
+/// partial document.
+pub struct Manifest {
+    dir: PathBuf,
+    files: Vec<ManifestEntry>,
+    hasher: Sha256,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+impl Manifest {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            files: Vec::new(),
+            hasher: Sha256::new(),
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    /// Account for `line` and its newline, written to the current file at
+    /// `timestamp`.
+    pub fn line(&mut self, line: &[u8], timestamp: DateTime<Utc>) {
+        self.hasher.update(line);
+        self.hasher.update(b"\n");
+        self.start_time = Some(self.start_time.map_or(timestamp, |start| start.min(timestamp)));
+        self.end_time = Some(self.end_time.map_or(timestamp, |end| end.max(timestamp)));
+    }
+
+    /// Close the current file of `lines` lines and `bytes` bytes, which is
+    /// now at `plain_file` and finally named `file`.
+    pub fn closed(&mut self, file: String, plain_file: String, lines: u64, bytes: u64) {
+        let hasher = std::mem::take(&mut self.hasher);
+        self.files.push(ManifestEntry {
+            file,
+            plain_file,
+            start_time: self.start_time.take(),
+            end_time: self.end_time.take(),
+            lines,
+            bytes,
+            sha256: format!("{:x}", hasher.finalize()),
+            deleted: false,
+        });
+    }
+
+    /// Follow files renamed on disk, like `Retention::renamed`.
+    pub fn renamed(&mut self, rename: impl Fn(&Path) -> Option<PathBuf>) {
+        let rename = |name: &mut String| {
+            if let Some(path) = rename(Path::new(name.as_str())) {
+                *name = path.to_string_lossy().into_owned();
+            }
+        };
+        for entry in self.files.iter_mut().filter(|entry| !entry.deleted) {
+            rename(&mut entry.file);
+            rename(&mut entry.plain_file);
+        }
+    }
+
+    /// Rewrite the manifest; `complete` once the service has finished.
+    pub fn save(&mut self, complete: bool) -> io::Result<()> {
+        // A file is gone only once neither name exists, since compression
+        // creates the final one before removing the plain one
+        for entry in self.files.iter_mut().filter(|entry| !entry.deleted) {
+            entry.deleted = !self.dir.join(&entry.file).exists() && !self.dir.join(&entry.plain_file).exists();
+        }
+        let path = self.dir.join(MANIFEST_FILE_NAME);
+        let partial = self.dir.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        let document = ManifestDocument {
+            complete,
+            files: &self.files,
+        };
+        serde_json::to_writer_pretty(&mut writer, &document)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&partial, &path)
+    }
+}
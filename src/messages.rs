@@ -1,28 +1,131 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
+use serde::Deserialize;
+
+use crate::latency::LatencyProfile;
+use crate::profiles::ServiceProfile;
 
-/// Log level with weighted distribution: INFO=500, WARN=10, ERROR=3
+/// Log level, from most to least verbose.
 #[derive(Clone, Copy)]
 pub enum LogLevel {
+    Trace,
+    Debug,
     Info,
     Warn,
     Error,
+    Fatal,
+}
+
+/// Relative weights of the log levels. Unset levels keep their default, which
+/// is the production-like INFO=500, WARN=10, ERROR=3 with no TRACE, DEBUG or
+/// FATAL lines.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LevelWeights {
+    pub trace: u32,
+    pub debug: u32,
+    pub info: u32,
+    pub warn: u32,
+    pub error: u32,
+    pub fatal: u32,
+}
+
+impl Default for LevelWeights {
+    fn default() -> Self {
+        Self {
+            trace: 0,
+            debug: 0,
+            info: 500,
+            warn: 10,
+            error: 3,
+            fatal: 0,
+        }
+    }
+}
+
+impl LevelWeights {
+    fn total(&self) -> u64 {
+        [self.trace, self.debug, self.info, self.warn, self.error, self.fatal]
+            .iter()
+            .map(|&w| w as u64)
+            .sum()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.total() {
+            0 => Err("level weights must not all be 0".into()),
+            total if total > u32::MAX as u64 => Err("level weights are too large".into()),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl LogLevel {
-    pub fn random(rng: &mut impl Rng) -> Self {
-        let n: u32 = rng.gen_range(0..513);
-        match n {
-            0..=2 => LogLevel::Error,
-            3..=12 => LogLevel::Warn,
-            _ => LogLevel::Info,
+    /// Pick a level according to `weights`.
+    pub fn weighted(rng: &mut impl Rng, weights: &LevelWeights) -> Self {
+        let mut n = rng.gen_range(0..weights.total() as u32);
+        // Rarest levels first, so the default weights draw exactly as the
+        // original fixed INFO/WARN/ERROR mix did
+        for (level, weight) in [
+            (LogLevel::Fatal, weights.fatal),
+            (LogLevel::Error, weights.error),
+            (LogLevel::Warn, weights.warn),
+            (LogLevel::Info, weights.info),
+            (LogLevel::Debug, weights.debug),
+            (LogLevel::Trace, weights.trace),
+        ] {
+            if n < weight {
+                return level;
+            }
+            n -= weight;
+        }
+        LogLevel::Info
+    }
+
+    /// RFC 5424 severity code used for the syslog PRI value.
+    pub fn syslog_severity(&self) -> u8 {
+        match self {
+            LogLevel::Trace | LogLevel::Debug => 7,
+            LogLevel::Info => 6,
+            LogLevel::Warn => 4,
+            LogLevel::Error => 3,
+            LogLevel::Fatal => 2,
         }
     }
 
     pub fn as_str(&self) -> &'static str {
         match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
             LogLevel::Info => "INFO",
             LogLevel::Warn => "WARN",
             LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        }
+    }
+
+    fn templates(&self) -> &'static [&'static str] {
+        match self {
+            LogLevel::Trace => TRACE_TEMPLATES,
+            LogLevel::Debug => DEBUG_TEMPLATES,
+            LogLevel::Info => unreachable!("INFO messages are picked per category"),
+            LogLevel::Warn => WARN_TEMPLATES,
+            LogLevel::Error => ERROR_TEMPLATES,
+            LogLevel::Fatal => FATAL_TEMPLATES,
+        }
+    }
+
+    fn categories(&self) -> &'static [&'static str] {
+        match self {
+            LogLevel::Trace => TRACE_CATEGORIES,
+            LogLevel::Debug => DEBUG_CATEGORIES,
+            LogLevel::Info => INFO_CATEGORIES,
+            LogLevel::Warn => WARN_CATEGORIES,
+            LogLevel::Error => ERROR_CATEGORIES,
+            LogLevel::Fatal => FATAL_CATEGORIES,
         }
     }
 }
@@ -55,6 +158,17 @@ const STATUS_CODES_OK: &[u16] = &[200, 201, 202, 204];
 const STATUS_CODES_WARN: &[u16] = &[301, 302, 304, 400, 401, 403, 404, 405, 408, 409, 429];
 const STATUS_CODES_ERR: &[u16] = &[500, 502, 503, 504];
 
+const HTTP_PROTOCOLS: &[&str] = &["HTTP/1.1", "HTTP/1.1", "HTTP/1.1", "HTTP/2.0", "HTTP/1.0"];
+
+const REFERERS: &[&str] = &[
+    "-", "-", "-",
+    "https://www.example.com/", "https://app.example.com/dashboard",
+    "https://app.example.com/checkout", "https://www.google.com/",
+    "https://www.bing.com/", "https://docs.example.com/api",
+];
+
+const REMOTE_USERS: &[&str] = &["-", "-", "-", "-", "-", "admin", "svc-reporting", "jdoe", "asmith"];
+
 const DB_TABLES: &[&str] = &[
     "users", "orders", "products", "sessions", "payments", "audit_log",
     "inventory", "shipments", "notifications", "events", "metrics",
@@ -92,7 +206,7 @@ const EXTERNAL_SERVICES: &[&str] = &[
     "Cloudflare", "Fastly CDN", "New Relic", "Sentry", "LaunchDarkly",
 ];
 
-const USER_AGENTS: &[&str] = &[
+pub const USER_AGENTS: &[&str] = &[
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
     "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15",
     "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36",
@@ -139,49 +253,92 @@ const STACK_FRAMES: &[&str] = &[
     "at java.base/java.lang.Thread.run(Thread.java:833)",
 ];
 
-const INFO_TEMPLATES: &[&str] = &[
-    "Request completed successfully",
-    "Database query executed",
-    "Cache operation completed",
-    "Message published to queue",
-    "Message consumed from queue",
-    "Health check passed",
-    "Configuration reloaded",
-    "External service call succeeded",
-    "Session validated for user",
-    "Batch job started",
-    "Batch job completed",
-    "Scheduled task executed",
-    "Connection pool stats reported",
-    "Feature flag evaluated",
-    "Metrics flushed to collector",
-    "Graceful shutdown initiated",
-    "Service instance registered with discovery",
-    "SSL certificate verified",
-    "Rate limit check passed",
-    "Distributed lock acquired",
-    "Distributed lock released",
-    "Data export completed",
-    "Search index updated",
-    "Webhook delivered successfully",
-    "Background worker processing item",
-    "Tenant context initialized",
-    "Circuit breaker status: CLOSED",
-    "Retry attempt succeeded",
-    "File upload completed",
-    "PDF report generated",
-    "Email notification queued",
-    "Push notification sent",
-    "User authentication successful",
-    "Token refresh completed",
-    "API key validated",
-    "CORS preflight request handled",
-    "Request rate within threshold",
-    "GC pause recorded",
-    "Thread pool utilization reported",
-    "Memory usage within bounds",
+const TRACE_TEMPLATES: &[&str] = &[
+    "Entering method",
+    "Exiting method",
+    "SQL statement prepared",
+    "SQL parameters bound",
+    "HTTP header parsed",
+    "Request body deserialized",
+    "Response body serialized",
+    "Connection borrowed from pool",
+    "Connection returned to pool",
+    "Interceptor chain invoked",
+    "Span context propagated",
+    "Routing rule evaluated",
+];
+
+const DEBUG_TEMPLATES: &[&str] = &[
+    "Cache lookup performed",
+    "Configuration value resolved",
+    "Query plan selected",
+    "Outgoing HTTP request prepared",
+    "Token claims decoded",
+    "Request payload validated",
+    "Retry policy evaluated",
+    "Feature flag cache refreshed",
+    "Bean initialized",
+    "Transaction boundary opened",
+    "Message headers inspected",
+    "Load balancer picked upstream instance",
 ];
 
+/// INFO messages of each category in `INFO_CATEGORIES`. Domain-specific
+/// messages, such as "PDF report generated", come from service profiles.
+const INFO_TEMPLATES: &[&[&str]] = &[
+    // http
+    &[
+        "Request completed successfully",
+        "CORS preflight request handled",
+        "Request rate within threshold",
+        "Rate limit check passed",
+    ],
+    // db
+    &["Database query executed", "Transaction committed"],
+    // cache
+    &["Cache operation completed", "Cache entry refreshed"],
+    // queue
+    &["Message published to queue", "Message consumed from queue", "Background worker processing item"],
+    // external
+    &["External service call succeeded", "Retry attempt succeeded", "Circuit breaker status: CLOSED"],
+    // health
+    &[
+        "Health check passed",
+        "Configuration reloaded",
+        "Connection pool stats reported",
+        "Graceful shutdown initiated",
+        "Service instance registered with discovery",
+        "GC pause recorded",
+        "Thread pool utilization reported",
+        "Memory usage within bounds",
+    ],
+    // auth
+    &[
+        "Session validated for user",
+        "User authentication successful",
+        "Token refresh completed",
+        "API key validated",
+        "SSL certificate verified",
+        "Tenant context initialized",
+    ],
+    // batch
+    &[
+        "Batch job started",
+        "Batch job completed",
+        "Scheduled task executed",
+        "Distributed lock acquired",
+        "Distributed lock released",
+    ],
+    // feature_flag
+    &["Feature flag evaluated"],
+    // metrics
+    &["Metrics flushed to collector"],
+];
+
+/// Batch messages other than the halves lifecycle pairs write together, used
+/// instead of the batch templates above while pairs are enabled.
+const UNPAIRED_BATCH_TEMPLATES: &[&str] = &["Scheduled task executed"];
+
 const WARN_TEMPLATES: &[&str] = &[
     "Slow query detected",
     "High memory utilization detected",
@@ -238,15 +395,68 @@ const ERROR_TEMPLATES: &[&str] = &[
     "Backup process failed",
 ];
 
+const FATAL_TEMPLATES: &[&str] = &[
+    "Unrecoverable error, shutting down",
+    "Out of memory: process terminating",
+    "Database migration failed, aborting startup",
+    "Required configuration missing, cannot start",
+    "Data corruption detected, halting writes",
+    "Panic in worker thread",
+    "Fatal: unable to bind to port",
+    "Storage volume unavailable, exiting",
+];
+
 const ADJECTIVES: &[&str] = &[
     "primary", "secondary", "cached", "stale", "partial", "complete",
     "encrypted", "compressed", "validated", "sanitized", "normalized",
     "aggregated", "batched", "streamed", "replicated", "sharded",
 ];
 
+/// Category names, indexed in the same order as the arms of the matching
+/// `generate_*_detail` function.
+const TRACE_CATEGORIES: &[&str] = &["method", "sql", "http_header", "pool"];
+const DEBUG_CATEGORIES: &[&str] = &["cache", "config", "query_plan", "http_client", "auth"];
+pub const INFO_CATEGORIES: &[&str] = &[
+    "http", "db", "cache", "queue", "external", "health", "auth", "batch",
+    "feature_flag", "metrics",
+];
+const WARN_CATEGORIES: &[&str] = &[
+    "db", "http", "pool", "external", "queue", "memory", "disk", "rate_limit",
+];
+const ERROR_CATEGORIES: &[&str] = &["http", "external", "db", "queue", "auth", "memory"];
+const FATAL_CATEGORIES: &[&str] = &["startup", "memory", "corruption", "panic"];
+
+const CLASS_NAMES: &[&str] = &[
+    "com.enterprise.service.UserService", "com.enterprise.service.OrderService",
+    "com.enterprise.repository.BaseRepository", "com.enterprise.controller.ApiController",
+    "com.enterprise.cache.CacheManager", "com.enterprise.http.RetryHandler",
+    "com.enterprise.validation.RequestValidator", "com.enterprise.queue.MessageConsumer",
+];
+
+const METHOD_NAMES: &[&str] = &[
+    "findById", "processOrder", "execute", "handleRequest", "get", "validate",
+    "onMessage", "serialize", "doFilter", "call",
+];
+
+const CONFIG_KEYS: &[&str] = &[
+    "db.pool.max_size", "http.client.timeout_ms", "cache.ttl_seconds",
+    "feature_flags.refresh_seconds", "queue.consumer.concurrency",
+    "auth.token.ttl_minutes", "retry.max_attempts", "server.max_body_bytes",
+];
+
+const BATCH_JOBS: &[&str] = &[
+    "nightly-reconciliation", "invoice-generation", "search-reindex",
+    "report-export", "data-retention-cleanup", "cache-warmup",
+    "usage-aggregation", "subscription-renewal",
+];
+
+const LOCK_RESOURCES: &[&str] = &["order", "inventory", "payment", "account", "invoice", "cart", "shipment"];
+
+pub const AUTH_PROVIDERS: &[&str] = &["oauth2", "saml", "ldap", "local", "oidc"];
+
 const IP_OCTETS: std::ops::Range<u8> = 1..255;
 
-fn random_ip(rng: &mut impl Rng) -> String {
+pub fn random_ip(rng: &mut impl Rng) -> String {
     format!(
         "{}.{}.{}.{}",
         rng.gen_range(IP_OCTETS),
@@ -256,15 +466,7 @@ fn random_ip(rng: &mut impl Rng) -> String {
     )
 }
 
-fn random_latency(rng: &mut impl Rng, level: LogLevel) -> u32 {
-    match level {
-        LogLevel::Info => rng.gen_range(1..500),
-        LogLevel::Warn => rng.gen_range(500..5000),
-        LogLevel::Error => rng.gen_range(3000..30000),
-    }
-}
-
-fn random_trace_id(rng: &mut impl Rng) -> String {
+pub fn random_trace_id(rng: &mut impl Rng) -> String {
     // Simplified trace ID (hex)
     format!(
         "{:08x}{:08x}{:08x}{:08x}",
@@ -275,11 +477,16 @@ fn random_trace_id(rng: &mut impl Rng) -> String {
     )
 }
 
-fn random_span_id(rng: &mut impl Rng) -> String {
+/// A v4 UUID drawn from `rng`, so seeded runs stay reproducible.
+pub fn random_uuid(rng: &mut impl Rng) -> uuid::Uuid {
+    uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+}
+
+pub fn random_span_id(rng: &mut impl Rng) -> String {
     format!("{:016x}", rng.gen::<u64>())
 }
 
-fn pick<'a>(rng: &mut impl Rng, items: &'a [&str]) -> &'a str {
+pub fn pick<'a>(rng: &mut impl Rng, items: &'a [&str]) -> &'a str {
     items[rng.gen_range(0..items.len())]
 }
 
@@ -287,293 +494,999 @@ fn pick_u16(rng: &mut impl Rng, items: &[u16]) -> u16 {
     items[rng.gen_range(0..items.len())]
 }
 
-/// Generate a realistic log message for the given level
-pub fn generate_message(rng: &mut impl Rng, level: LogLevel, service_name: &str) -> String {
+/// A typed value for a structured detail field.
+pub enum FieldValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Percent(f64),
+}
+
+impl From<&str> for FieldValue {
+    fn from(v: &str) -> Self {
+        FieldValue::Str(v.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(v: String) -> Self {
+        FieldValue::Str(v)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(v: bool) -> Self {
+        FieldValue::Bool(v)
+    }
+}
+
+impl From<uuid::Uuid> for FieldValue {
+    fn from(v: uuid::Uuid) -> Self {
+        FieldValue::Str(v.to_string())
+    }
+}
+
+macro_rules! field_value_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for FieldValue {
+            fn from(v: $t) -> Self {
+                FieldValue::Int(v as i64)
+            }
+        })*
+    };
+}
+
+field_value_from_int!(u16, u32, u64, i32, i64);
+
+/// Ordered key/value detail fields of a single log event.
+pub type Fields = Vec<(&'static str, FieldValue)>;
+
+/// A single generated log event, independent of how it is rendered.
+pub struct LogEvent<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    pub service: &'a str,
+    pub trace_id: String,
+    pub span_id: String,
+    pub thread: u32,
+    /// Short machine-friendly name of the detail category, e.g. `http` or `db`.
+    pub category: &'static str,
+    pub template: &'a str,
+    pub fields: Fields,
+    pub stack_trace: Option<Vec<&'static str>>,
+}
+
+impl LogEvent<'_> {
+    /// Look up a detail field by key.
+    pub fn field(&self, key: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Replace a detail field, or append it if the event does not have it.
+    pub fn set_field(&mut self, key: &'static str, value: FieldValue) {
+        match self.fields.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.fields.push((key, value)),
+        }
+    }
+}
+
+/// The service events are generated for: its name, what it logs about, and
+/// how long its operations take.
+pub struct ServiceContext<'a> {
+    pub name: &'a str,
+    pub profile: &'a ServiceProfile,
+    pub latency: &'a LatencyProfile,
+    /// Whether lifecycle pairs are enabled, so single INFO lines must not
+    /// log one half of a pair on their own.
+    pub lifecycle_pairs: bool,
+}
+
+impl<'a> ServiceContext<'a> {
+    fn path(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.paths, API_PATHS)
+    }
+
+    fn table(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.tables, DB_TABLES)
+    }
+
+    fn queue(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.queues, QUEUE_NAMES)
+    }
+
+    fn cache_key(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.cache_keys, CACHE_KEYS)
+    }
+
+    fn external_service(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.external_services, EXTERNAL_SERVICES)
+    }
+
+    fn batch_job(&self, rng: &mut impl Rng) -> &'a str {
+        pick_vocabulary(rng, &self.profile.batch_jobs, BATCH_JOBS)
+    }
+
+    /// Index into `INFO_CATEGORIES`, following the profile's weights.
+    /// Without weights every INFO message is equally likely.
+    fn info_category(&self, rng: &mut impl Rng) -> usize {
+        let weights = &self.profile.categories;
+        if weights.is_empty() {
+            let total = (0..INFO_CATEGORIES.len()).map(|i| self.info_templates(i).0).sum();
+            let mut n = rng.gen_range(0..total);
+            for category in 0..INFO_CATEGORIES.len() {
+                let (count, _) = self.info_templates(category);
+                if n < count {
+                    return category;
+                }
+                n -= count;
+            }
+        } else {
+            let mut n = rng.gen_range(0..weights.values().sum::<u32>());
+            for (name, &weight) in weights {
+                if n < weight {
+                    return INFO_CATEGORIES.iter().position(|c| c == name).expect("validated category");
+                }
+                n -= weight;
+            }
+        }
+        unreachable!("n is below the total weight")
+    }
+
+    /// Built-in INFO messages of `category`.
+    fn shared_info_templates(&self, category: usize) -> &'static [&'static str] {
+        if self.lifecycle_pairs && INFO_CATEGORIES[category] == "batch" {
+            UNPAIRED_BATCH_TEMPLATES
+        } else {
+            INFO_TEMPLATES[category]
+        }
+    }
+
+    /// Number of INFO messages of `category`, and the service's own ones.
+    fn info_templates(&self, category: usize) -> (usize, &'a [String]) {
+        let own = self.profile.messages.get(INFO_CATEGORIES[category]).map_or(&[][..], Vec::as_slice);
+        (self.shared_info_templates(category).len() + own.len(), own)
+    }
+
+    fn info_template(&self, rng: &mut impl Rng, category: usize) -> &'a str {
+        let shared = self.shared_info_templates(category);
+        let (count, own) = self.info_templates(category);
+        let n = rng.gen_range(0..count);
+        match shared.get(n) {
+            Some(template) => template,
+            None => &own[n - shared.len()],
+        }
+    }
+}
+
+/// Pick from a service's own vocabulary, or from the shared pool if it has none.
+fn pick_vocabulary<'a>(rng: &mut impl Rng, own: &'a [String], shared: &'static [&'static str]) -> &'a str {
+    if own.is_empty() {
+        pick(rng, shared)
+    } else {
+        &own[rng.gen_range(0..own.len())]
+    }
+}
+
+/// Generate a realistic log event for the given level
+pub fn generate_event<'a>(
+    rng: &mut impl Rng,
+    level: LogLevel,
+    ctx: &ServiceContext<'a>,
+    timestamp: DateTime<Utc>,
+) -> LogEvent<'a> {
     let trace_id = random_trace_id(rng);
     let span_id = random_span_id(rng);
-    let thread_id = rng.gen_range(1..128);
+    let thread = rng.gen_range(1..128);
 
-    let (template, detail) = match level {
+    let (template, (category, fields, stack_trace)) = match level {
+        // The service profile decides what a service logs about
         LogLevel::Info => {
-            let t = pick(rng, INFO_TEMPLATES);
-            let d = generate_info_detail(rng);
-            (t, d)
+            let index = ctx.info_category(rng);
+            let template = ctx.info_template(rng, index);
+            let (category, fields) = generate_info_detail(rng, index, ctx);
+            (template, (category, fields, None))
         }
-        LogLevel::Warn => {
-            let t = pick(rng, WARN_TEMPLATES);
-            let d = generate_warn_detail(rng);
-            (t, d)
+        _ => (pick(rng, level.templates()), generate_detail(rng, level, None, timestamp, ctx)),
+    };
+    // Latency decides which operations are slow: an INFO line past its
+    // category's threshold is logged as the matching slow warning instead
+    if let (LogLevel::Info, Some(template)) = (level, ctx.latency.slow_warning(category, &fields)) {
+        return generate_event_as(rng, LogLevel::Warn, ctx, timestamp, category, template);
+    }
+
+    LogEvent {
+        timestamp,
+        level,
+        service: ctx.name,
+        trace_id,
+        span_id,
+        thread,
+        category,
+        template,
+        fields,
+        stack_trace,
+    }
+}
+
+/// Generate an event with a fixed message and detail category (e.g. `db`,
+/// `external`), so injected incidents and traces read consistently. A
+/// category the level does not have falls back to a random one.
+pub fn generate_event_as<'a>(
+    rng: &mut impl Rng,
+    level: LogLevel,
+    ctx: &ServiceContext<'a>,
+    timestamp: DateTime<Utc>,
+    category: &str,
+    template: &'a str,
+) -> LogEvent<'a> {
+    let trace_id = random_trace_id(rng);
+    let span_id = random_span_id(rng);
+    let thread = rng.gen_range(1..128);
+
+    let (category, fields, stack_trace) = generate_detail(rng, level, Some(category), timestamp, ctx);
+
+    LogEvent {
+        timestamp,
+        level,
+        service: ctx.name,
+        trace_id,
+        span_id,
+        thread,
+        category,
+        template,
+        fields,
+        stack_trace,
+    }
+}
+
+/// Detail fields for an event of `level`, in the named category or a random
+/// one.
+fn generate_detail(
+    rng: &mut impl Rng,
+    level: LogLevel,
+    category: Option<&str>,
+    timestamp: DateTime<Utc>,
+    ctx: &ServiceContext,
+) -> (&'static str, Fields, Option<Vec<&'static str>>) {
+    let categories = level.categories();
+    let index = match category {
+        Some(name) => category_index(rng, categories, name),
+        None => rng.gen_range(0..categories.len()),
+    };
+    let without_stack = |(category, fields)| (category, fields, None);
+    match level {
+        LogLevel::Trace => without_stack(generate_trace_detail(rng, index, ctx)),
+        LogLevel::Debug => without_stack(generate_debug_detail(rng, index, ctx)),
+        LogLevel::Info => without_stack(generate_info_detail(rng, index, ctx)),
+        LogLevel::Warn => without_stack(generate_warn_detail(rng, index, ctx)),
+        LogLevel::Error => generate_error_detail(rng, index, timestamp, ctx),
+        LogLevel::Fatal => generate_fatal_detail(rng, index, ctx),
+    }
+}
+
+fn category_index(rng: &mut impl Rng, categories: &[&str], name: &str) -> usize {
+    categories
+        .iter()
+        .position(|c| *c == name)
+        .unwrap_or_else(|| rng.gen_range(0..categories.len()))
+}
+
+/// Generate a start line and its matching end line, e.g. "Request received"
+/// and "Request completed successfully". Both share an id (`request_id`,
+/// `job_id` or `lock_id`) and trace context; the end line is stamped later by
+/// exactly the duration it reports, which is at most `max_duration` so the
+/// pair completes before the run ends.
+pub fn generate_lifecycle_pair<'a>(
+    rng: &mut impl Rng,
+    ctx: &ServiceContext<'a>,
+    timestamp: DateTime<Utc>,
+    max_duration: Option<Duration>,
+) -> (LogEvent<'a>, LogEvent<'a>) {
+    let clamp = |duration_ms: i64| match max_duration {
+        Some(max) => duration_ms.min(max.num_milliseconds()).max(0),
+        None => duration_ms,
+    };
+    let trace_id = random_trace_id(rng);
+    let span_id = random_span_id(rng);
+    let thread = rng.gen_range(1..128);
+
+    let (category, start_template, start_fields, end_template, duration_ms, end_fields): (_, _, Fields, _, i64, Fields) =
+        match rng.gen_range(0..3) {
+            0 => {
+                let method = pick(rng, HTTP_METHODS);
+                let path = ctx.path(rng);
+                let request_id = random_uuid(rng);
+                let duration_ms = clamp(ctx.latency.http.sample(rng) as i64);
+                (
+                    "http",
+                    "Request received",
+                    vec![
+                        ("method", method.into()),
+                        ("path", path.into()),
+                        ("client_ip", random_ip(rng).into()),
+                        ("user_agent", pick(rng, USER_AGENTS).into()),
+                        ("request_id", request_id.into()),
+                    ],
+                    "Request completed successfully",
+                    duration_ms,
+                    vec![
+                        ("method", method.into()),
+                        ("path", path.into()),
+                        ("status", pick_u16(rng, STATUS_CODES_OK).into()),
+                        ("duration_ms", duration_ms.into()),
+                        ("response_bytes", rng.gen_range(50..50000).into()),
+                        ("request_id", request_id.into()),
+                    ],
+                )
+            }
+            1 => {
+                let job_id = random_uuid(rng);
+                let job_name = ctx.batch_job(rng);
+                let items: u32 = rng.gen_range(1..100000);
+                let duration_ms = clamp(rng.gen_range(10_000..1_800_000));
+                (
+                    "batch",
+                    "Batch job started",
+                    vec![
+                        ("job_id", job_id.into()),
+                        ("job_name", job_name.into()),
+                        ("items_total", items.into()),
+                    ],
+                    "Batch job completed",
+                    duration_ms,
+                    vec![
+                        ("job_id", job_id.into()),
+                        ("job_name", job_name.into()),
+                        ("items_processed", items.into()),
+                        ("duration_ms", duration_ms.into()),
+                    ],
+                )
+            }
+            _ => {
+                let lock_id = random_uuid(rng);
+                let lock_key = format!("lock:{}:{}", pick(rng, LOCK_RESOURCES), rng.gen_range(1..100000));
+                let duration_ms = clamp(rng.gen_range(1..5000));
+                (
+                    "lock",
+                    "Distributed lock acquired",
+                    vec![
+                        ("lock_key", lock_key.clone().into()),
+                        ("lock_id", lock_id.into()),
+                        ("ttl_ms", 30000.into()),
+                    ],
+                    "Distributed lock released",
+                    duration_ms,
+                    vec![
+                        ("lock_key", lock_key.into()),
+                        ("lock_id", lock_id.into()),
+                        ("held_ms", duration_ms.into()),
+                    ],
+                )
+            }
+        };
+
+    let start = LogEvent {
+        timestamp,
+        level: LogLevel::Info,
+        service: ctx.name,
+        trace_id: trace_id.clone(),
+        span_id: span_id.clone(),
+        thread,
+        category,
+        template: start_template,
+        fields: start_fields,
+        stack_trace: None,
+    };
+    let end = LogEvent {
+        timestamp: timestamp + Duration::milliseconds(duration_ms),
+        level: LogLevel::Info,
+        service: ctx.name,
+        trace_id,
+        span_id,
+        thread,
+        category,
+        template: end_template,
+        fields: end_fields,
+        stack_trace: None,
+    };
+    (start, end)
+}
+
+/// Generate a single served HTTP request, as written by a web server or
+/// gateway access log. The level decides the status code class.
+pub fn generate_access_event<'a>(
+    rng: &mut impl Rng,
+    level: LogLevel,
+    ctx: &ServiceContext<'a>,
+    timestamp: DateTime<Utc>,
+) -> LogEvent<'a> {
+    let status = match level {
+        LogLevel::Trace | LogLevel::Debug | LogLevel::Info => pick_u16(rng, STATUS_CODES_OK),
+        LogLevel::Warn => pick_u16(rng, STATUS_CODES_WARN),
+        LogLevel::Error | LogLevel::Fatal => pick_u16(rng, STATUS_CODES_ERR),
+    };
+    let latency_ms = match level {
+        LogLevel::Trace | LogLevel::Debug | LogLevel::Info => ctx.latency.http.sample(rng),
+        LogLevel::Warn | LogLevel::Error | LogLevel::Fatal => ctx.latency.http.sample_slow(rng),
+    };
+    let response_bytes = match status {
+        204 | 304 => 0,
+        _ => rng.gen_range(50..50000),
+    };
+    let fields: Fields = vec![
+        ("method", pick(rng, HTTP_METHODS).into()),
+        ("path", ctx.path(rng).into()),
+        ("protocol", pick(rng, HTTP_PROTOCOLS).into()),
+        ("status", status.into()),
+        ("latency_ms", latency_ms.into()),
+        ("client_ip", random_ip(rng).into()),
+        ("remote_user", pick(rng, REMOTE_USERS).into()),
+        ("referer", pick(rng, REFERERS).into()),
+        ("user_agent", pick(rng, USER_AGENTS).into()),
+        ("response_bytes", response_bytes.into()),
+    ];
+
+    LogEvent {
+        timestamp,
+        level,
+        service: ctx.name,
+        trace_id: random_trace_id(rng),
+        span_id: random_span_id(rng),
+        thread: rng.gen_range(1..128),
+        category: "http",
+        template: "HTTP request served",
+        fields,
+        stack_trace: None,
+    }
+}
+
+fn generate_trace_detail(rng: &mut impl Rng, category: usize, ctx: &ServiceContext) -> (&'static str, Fields) {
+    let fields = match category {
+        0 => {
+            vec![
+                ("class", pick(rng, CLASS_NAMES).into()),
+                ("method", pick(rng, METHOD_NAMES).into()),
+                ("args", rng.gen_range(0..6).into()),
+                ("elapsed_us", rng.gen_range(1..2000).into()),
+            ]
         }
-        LogLevel::Error => {
-            let t = pick(rng, ERROR_TEMPLATES);
-            let d = generate_error_detail(rng);
-            (t, d)
+        1 => {
+            let table = ctx.table(rng);
+            let statement = match rng.gen_range(0..3) {
+                0 => format!("SELECT * FROM {} WHERE id = ?", table),
+                1 => format!("UPDATE {} SET updated_at = ? WHERE id = ?", table),
+                _ => format!("INSERT INTO {} VALUES (?, ?, ?)", table),
+            };
+            vec![
+                ("statement", statement.into()),
+                ("parameters", rng.gen_range(1..8).into()),
+                ("prepare_us", rng.gen_range(5..500).into()),
+            ]
+        }
+        2 => {
+            let headers = &["Accept", "Authorization", "Content-Type", "X-Request-Id", "X-Forwarded-For", "traceparent", "User-Agent"];
+            vec![
+                ("header", pick(rng, headers).into()),
+                ("value_length", rng.gen_range(3..512).into()),
+                ("request_id", random_uuid(rng).into()),
+            ]
+        }
+        _ => {
+            vec![
+                ("pool", pick(rng, &["primary-db", "replica-db", "redis", "http-client"]).into()),
+                ("connection_id", rng.gen_range(1..1000).into()),
+                ("wait_us", rng.gen_range(0..5000).into()),
+                ("active", rng.gen_range(1..100).into()),
+            ]
         }
     };
 
-    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+    (TRACE_CATEGORIES[category], fields)
+}
 
-    format!(
-        "{timestamp} [{level}] [{service}] [trace_id={trace_id}] [span_id={span_id}] [thread={thread}] {template} | {detail}",
-        timestamp = timestamp,
-        level = level.as_str(),
-        service = service_name,
-        trace_id = trace_id,
-        span_id = span_id,
-        thread = format!("worker-{}", thread_id),
-        template = template,
-        detail = detail,
-    )
+fn generate_debug_detail(rng: &mut impl Rng, category: usize, ctx: &ServiceContext) -> (&'static str, Fields) {
+    let fields = match category {
+        0 => {
+            vec![
+                ("cache_key", ctx.cache_key(rng).into()),
+                ("hit", rng.gen_bool(0.8).into()),
+                ("lookup_us", rng.gen_range(10..3000).into()),
+                ("ttl_seconds", rng.gen_range(60..86400).into()),
+            ]
+        }
+        1 => {
+            vec![
+                ("key", pick(rng, CONFIG_KEYS).into()),
+                ("value", rng.gen_range(1..10000).to_string().into()),
+                ("source", pick(rng, &["env", "file", "consul", "vault", "default"]).into()),
+            ]
+        }
+        2 => {
+            let plans = &["Index Scan", "Index Only Scan", "Seq Scan", "Bitmap Heap Scan", "Nested Loop", "Hash Join"];
+            vec![
+                ("table", ctx.table(rng).into()),
+                ("plan", pick(rng, plans).into()),
+                ("estimated_rows", rng.gen_range(1..100000).into()),
+                ("estimated_cost", rng.gen_range(1..50000).into()),
+            ]
+        }
+        3 => {
+            vec![
+                ("external_service", ctx.external_service(rng).into()),
+                ("method", pick(rng, HTTP_METHODS).into()),
+                ("timeout_ms", pick_u16(rng, &[1000, 2000, 5000, 10000, 30000]).into()),
+                ("attempt", rng.gen_range(1..4).into()),
+            ]
+        }
+        _ => {
+            let scopes = &["read", "read write", "admin", "read:orders write:orders", "openid profile email"];
+            vec![
+                ("user_id", random_uuid(rng).into()),
+                ("scopes", pick(rng, scopes).into()),
+                ("token_expires_in_seconds", rng.gen_range(60..3600).into()),
+                ("auth_provider", pick(rng, AUTH_PROVIDERS).into()),
+            ]
+        }
+    };
+
+    (DEBUG_CATEGORIES[category], fields)
 }
 
-fn generate_info_detail(rng: &mut impl Rng) -> String {
-    let category: u32 = rng.gen_range(0..10);
-    match category {
+fn generate_info_detail(rng: &mut impl Rng, category: usize, ctx: &ServiceContext) -> (&'static str, Fields) {
+    let fields = match category {
         0 => {
             // HTTP request
             let method = pick(rng, HTTP_METHODS);
-            let path = pick(rng, API_PATHS);
+            let path = ctx.path(rng);
             let status = pick_u16(rng, STATUS_CODES_OK);
-            let latency = random_latency(rng, LogLevel::Info);
+            let latency = ctx.latency.http.sample(rng);
             let ip = random_ip(rng);
             let ua = pick(rng, USER_AGENTS);
             let bytes = rng.gen_range(50..50000);
-            format!(
-                "method={} path={} status={} latency_ms={} client_ip={} user_agent=\"{}\" response_bytes={}",
-                method, path, status, latency, ip, ua, bytes
-            )
+            vec![
+                ("method", method.into()),
+                ("path", path.into()),
+                ("status", status.into()),
+                ("latency_ms", latency.into()),
+                ("client_ip", ip.into()),
+                ("user_agent", ua.into()),
+                ("response_bytes", bytes.into()),
+            ]
         }
         1 => {
             // Database query
             let op = pick(rng, DB_OPERATIONS);
-            let table = pick(rng, DB_TABLES);
+            let table = ctx.table(rng);
             let rows = rng.gen_range(0..10000);
-            let latency = random_latency(rng, LogLevel::Info);
+            let latency = ctx.latency.db.sample(rng);
             let adj = pick(rng, ADJECTIVES);
-            format!(
-                "operation={} table={} rows_affected={} query_time_ms={} data_type={} connection_pool_active={}",
-                op, table, rows, latency, adj, rng.gen_range(1..50)
-            )
+            vec![
+                ("operation", op.into()),
+                ("table", table.into()),
+                ("rows_affected", rows.into()),
+                ("query_time_ms", latency.into()),
+                ("data_type", adj.into()),
+                ("connection_pool_active", rng.gen_range(1..50).into()),
+            ]
         }
         2 => {
             // Cache operation
-            let key = pick(rng, CACHE_KEYS);
+            let key = ctx.cache_key(rng);
             let hit = rng.gen_bool(0.8);
             let ttl = rng.gen_range(60..86400);
-            format!(
-                "cache_key={} hit={} ttl_seconds={} size_bytes={} region={}",
-                key, hit, ttl, rng.gen_range(100..100000), pick(rng, &["us-east-1", "us-west-2", "eu-west-1", "ap-southeast-1"])
-            )
+            vec![
+                ("cache_key", key.into()),
+                ("hit", hit.into()),
+                ("ttl_seconds", ttl.into()),
+                ("size_bytes", rng.gen_range(100..100000).into()),
+                ("region", pick(rng, &["us-east-1", "us-west-2", "eu-west-1", "ap-southeast-1"]).into()),
+            ]
         }
         3 => {
             // Queue operation
-            let queue = pick(rng, QUEUE_NAMES);
+            let queue = ctx.queue(rng);
             let depth = rng.gen_range(0..5000);
             let consumer_lag = rng.gen_range(0..100);
-            format!(
-                "queue={} action=publish depth={} consumer_lag={} partition={} message_size_bytes={}",
-                queue, depth, consumer_lag, rng.gen_range(0..12), rng.gen_range(100..10000)
-            )
+            vec![
+                ("queue", queue.into()),
+                ("action", "publish".into()),
+                ("depth", depth.into()),
+                ("consumer_lag", consumer_lag.into()),
+                ("partition", rng.gen_range(0..12).into()),
+                ("message_size_bytes", rng.gen_range(100..10000).into()),
+            ]
         }
         4 => {
             // External service call
-            let svc = pick(rng, EXTERNAL_SERVICES);
-            let latency = random_latency(rng, LogLevel::Info);
-            format!(
-                "external_service=\"{}\" method=GET latency_ms={} status=200 retries=0 circuit_state=CLOSED",
-                svc, latency
-            )
+            let svc = ctx.external_service(rng);
+            let latency = ctx.latency.external.sample(rng);
+            vec![
+                ("external_service", svc.into()),
+                ("method", "GET".into()),
+                ("latency_ms", latency.into()),
+                ("status", 200.into()),
+                ("retries", 0.into()),
+                ("circuit_state", "CLOSED".into()),
+            ]
         }
         5 => {
             // Health check
             let uptime = rng.gen_range(1..365 * 24 * 3600);
-            let cpu = rng.gen_range(1..80);
-            let mem = rng.gen_range(20..80);
-            format!(
-                "uptime_seconds={} cpu_usage={}% memory_usage={}% gc_pause_ms={} active_threads={} open_connections={}",
-                uptime, cpu, mem, rng.gen_range(1..50), rng.gen_range(5..200), rng.gen_range(1..100)
-            )
+            let cpu: u32 = rng.gen_range(1..80);
+            let mem: u32 = rng.gen_range(20..80);
+            vec![
+                ("uptime_seconds", uptime.into()),
+                ("cpu_usage", FieldValue::Percent(cpu as f64)),
+                ("memory_usage", FieldValue::Percent(mem as f64)),
+                ("gc_pause_ms", rng.gen_range(1..50).into()),
+                ("active_threads", rng.gen_range(5..200).into()),
+                ("open_connections", rng.gen_range(1..100).into()),
+            ]
         }
         6 => {
             // User/Auth event
-            let user_id = uuid::Uuid::new_v4();
+            let user_id = random_uuid(rng);
             let ip = random_ip(rng);
             let actions = &["login", "logout", "token_refresh", "password_change", "mfa_verify", "api_key_rotate"];
             let action = pick(rng, actions);
-            format!(
-                "user_id={} action={} client_ip={} session_duration_ms={} auth_provider={}",
-                user_id, action, ip, rng.gen_range(0..86400000u64), pick(rng, &["oauth2", "saml", "ldap", "local", "oidc"])
-            )
+            vec![
+                ("user_id", user_id.into()),
+                ("action", action.into()),
+                ("client_ip", ip.into()),
+                ("session_duration_ms", rng.gen_range(0..86400000u64).into()),
+                ("auth_provider", pick(rng, AUTH_PROVIDERS).into()),
+            ]
         }
         7 => {
             // Batch/scheduled
-            let job_id = uuid::Uuid::new_v4();
+            let job_id = random_uuid(rng);
             let items = rng.gen_range(1..100000);
-            format!(
-                "job_id={} items_processed={} duration_ms={} success_rate={:.2}% next_run_in_seconds={}",
-                job_id, items, rng.gen_range(100..300000), rng.gen_range(9500..10000) as f64 / 100.0, rng.gen_range(60..3600)
-            )
+            vec![
+                ("job_id", job_id.into()),
+                ("items_processed", items.into()),
+                ("duration_ms", rng.gen_range(100..300000).into()),
+                ("success_rate", FieldValue::Percent(rng.gen_range(9500..10000) as f64 / 100.0)),
+                ("next_run_in_seconds", rng.gen_range(60..3600).into()),
+            ]
         }
         8 => {
             // Feature flag
             let flags = &["dark_mode", "new_checkout_flow", "beta_search", "ai_recommendations", "v2_pricing", "graphql_gateway"];
             let flag = pick(rng, flags);
             let enabled = rng.gen_bool(0.7);
-            format!(
-                "feature_flag={} enabled={} variant={} user_segment={} evaluation_ms={}",
-                flag, enabled, pick(rng, &["control", "treatment_a", "treatment_b"]),
-                pick(rng, &["enterprise", "pro", "free", "trial", "internal"]),
-                rng.gen_range(0..5)
-            )
+            vec![
+                ("feature_flag", flag.into()),
+                ("enabled", enabled.into()),
+                ("variant", pick(rng, &["control", "treatment_a", "treatment_b"]).into()),
+                ("user_segment", pick(rng, &["enterprise", "pro", "free", "trial", "internal"]).into()),
+                ("evaluation_ms", rng.gen_range(0..5).into()),
+            ]
         }
         _ => {
             // Metrics flush
             let metrics_count = rng.gen_range(50..5000);
-            format!(
-                "metrics_flushed={} flush_duration_ms={} dropped={} destination={} batch_size={}",
-                metrics_count, rng.gen_range(10..500), rng.gen_range(0..5),
-                pick(rng, &["datadog", "prometheus", "graphite", "influxdb", "cloudwatch"]),
-                rng.gen_range(100..1000)
-            )
+            vec![
+                ("metrics_flushed", metrics_count.into()),
+                ("flush_duration_ms", rng.gen_range(10..500).into()),
+                ("dropped", rng.gen_range(0..5).into()),
+                ("destination", pick(rng, &["datadog", "prometheus", "graphite", "influxdb", "cloudwatch"]).into()),
+                ("batch_size", rng.gen_range(100..1000).into()),
+            ]
         }
-    }
+    };
+
+    (INFO_CATEGORIES[category], fields)
 }
 
-fn generate_warn_detail(rng: &mut impl Rng) -> String {
-    let category: u32 = rng.gen_range(0..8);
-    match category {
+fn generate_warn_detail(rng: &mut impl Rng, category: usize, ctx: &ServiceContext) -> (&'static str, Fields) {
+    let fields = match category {
         0 => {
             let op = pick(rng, DB_OPERATIONS);
-            let table = pick(rng, DB_TABLES);
-            let latency = random_latency(rng, LogLevel::Warn);
-            format!(
-                "operation={} table={} query_time_ms={} threshold_ms=500 rows_scanned={} missing_index=true",
-                op, table, latency, rng.gen_range(10000..1000000)
-            )
+            let table = ctx.table(rng);
+            let query_time = ctx.latency.db.sample_slow(rng);
+            vec![
+                ("operation", op.into()),
+                ("table", table.into()),
+                ("query_time_ms", query_time.into()),
+                ("threshold_ms", (ctx.latency.db.threshold_ms().round() as u32).into()),
+                ("rows_scanned", rng.gen_range(10000..1000000).into()),
+                ("missing_index", true.into()),
+            ]
         }
         1 => {
             let method = pick(rng, HTTP_METHODS);
-            let path = pick(rng, API_PATHS);
+            let path = ctx.path(rng);
             let status = pick_u16(rng, STATUS_CODES_WARN);
-            let latency = random_latency(rng, LogLevel::Warn);
-            format!(
-                "method={} path={} status={} latency_ms={} client_ip={} retry_after_seconds={}",
-                method, path, status, latency, random_ip(rng), rng.gen_range(1..60)
-            )
+            let latency_ms = ctx.latency.http.sample_slow(rng);
+            vec![
+                ("method", method.into()),
+                ("path", path.into()),
+                ("status", status.into()),
+                ("latency_ms", latency_ms.into()),
+                ("threshold_ms", (ctx.latency.http.threshold_ms().round() as u32).into()),
+                ("client_ip", random_ip(rng).into()),
+                ("retry_after_seconds", rng.gen_range(1..60).into()),
+            ]
         }
         2 => {
-            let pool_size = rng.gen_range(50..200);
+            let pool_size: u32 = rng.gen_range(50..200);
             let active = pool_size - rng.gen_range(1..5);
-            format!(
-                "pool_size={} active_connections={} idle={} wait_queue={} max_wait_ms={}",
-                pool_size, active, pool_size - active, rng.gen_range(5..50), rng.gen_range(100..5000)
-            )
+            vec![
+                ("pool_size", pool_size.into()),
+                ("active_connections", active.into()),
+                ("idle", (pool_size - active).into()),
+                ("wait_queue", rng.gen_range(5..50).into()),
+                ("max_wait_ms", rng.gen_range(100..5000).into()),
+            ]
         }
         3 => {
-            let svc = pick(rng, EXTERNAL_SERVICES);
-            let latency = random_latency(rng, LogLevel::Warn);
-            format!(
-                "external_service=\"{}\" latency_ms={} expected_max_ms=1000 status=200 degraded=true retry_count={}",
-                svc, latency, rng.gen_range(1..3)
-            )
+            let svc = ctx.external_service(rng);
+            let latency_ms = ctx.latency.external.sample_slow(rng);
+            vec![
+                ("external_service", svc.into()),
+                ("latency_ms", latency_ms.into()),
+                ("expected_max_ms", (ctx.latency.external.threshold_ms().round() as u32).into()),
+                ("status", 200.into()),
+                ("degraded", true.into()),
+                ("retry_count", rng.gen_range(1..3).into()),
+            ]
         }
         4 => {
-            let queue = pick(rng, QUEUE_NAMES);
-            format!(
-                "queue={} depth={} max_depth=10000 consumer_lag_seconds={} oldest_message_age_seconds={}",
-                queue, rng.gen_range(5000..9500), rng.gen_range(30..300), rng.gen_range(60..600)
-            )
+            let queue = ctx.queue(rng);
+            vec![
+                ("queue", queue.into()),
+                ("depth", rng.gen_range(5000..9500).into()),
+                ("max_depth", 10000.into()),
+                ("consumer_lag_seconds", rng.gen_range(30..300).into()),
+                ("oldest_message_age_seconds", rng.gen_range(60..600).into()),
+            ]
         }
         5 => {
-            format!(
-                "memory_usage={}% threshold=85% heap_used_mb={} heap_max_mb={} gc_collections={} gc_time_ms={}",
-                rng.gen_range(80..95), rng.gen_range(3000..7500), 8192, rng.gen_range(100..1000), rng.gen_range(500..5000)
-            )
+            vec![
+                ("memory_usage", FieldValue::Percent(rng.gen_range(80..95) as f64)),
+                ("threshold", FieldValue::Percent(85.0)),
+                ("heap_used_mb", rng.gen_range(3000..7500).into()),
+                ("heap_max_mb", 8192.into()),
+                ("gc_collections", rng.gen_range(100..1000).into()),
+                ("gc_time_ms", rng.gen_range(500..5000).into()),
+            ]
         }
         6 => {
-            format!(
-                "disk_usage={}% partition=/data available_gb={} inode_usage={}% oldest_file_days={}",
-                rng.gen_range(80..95), rng.gen_range(5..50), rng.gen_range(60..90), rng.gen_range(30..365)
-            )
+            vec![
+                ("disk_usage", FieldValue::Percent(rng.gen_range(80..95) as f64)),
+                ("partition", "/data".into()),
+                ("available_gb", rng.gen_range(5..50).into()),
+                ("inode_usage", FieldValue::Percent(rng.gen_range(60..90) as f64)),
+                ("oldest_file_days", rng.gen_range(30..365).into()),
+            ]
         }
         _ => {
             let ip = random_ip(rng);
-            format!(
-                "client_ip={} requests_per_minute={} limit=1000 remaining={} window_reset_seconds={}",
-                ip, rng.gen_range(800..999), rng.gen_range(1..200), rng.gen_range(10..60)
-            )
+            vec![
+                ("client_ip", ip.into()),
+                ("requests_per_minute", rng.gen_range(800..999).into()),
+                ("limit", 1000.into()),
+                ("remaining", rng.gen_range(1..200).into()),
+                ("window_reset_seconds", rng.gen_range(10..60).into()),
+            ]
         }
-    }
+    };
+
+    (WARN_CATEGORIES[category], fields)
 }
 
-fn generate_error_detail(rng: &mut impl Rng) -> String {
-    let category: u32 = rng.gen_range(0..6);
+fn generate_error_detail(
+    rng: &mut impl Rng,
+    category: usize,
+    timestamp: DateTime<Utc>,
+    ctx: &ServiceContext,
+) -> (&'static str, Fields, Option<Vec<&'static str>>) {
     let include_stack = rng.gen_bool(0.4);
 
     let detail = match category {
         0 => {
             let method = pick(rng, HTTP_METHODS);
-            let path = pick(rng, API_PATHS);
+            let path = ctx.path(rng);
             let status = pick_u16(rng, STATUS_CODES_ERR);
             let err = pick(rng, ERROR_TYPES);
-            format!(
-                "method={} path={} status={} error_type={} latency_ms={} request_id={}",
-                method, path, status, err, random_latency(rng, LogLevel::Error), uuid::Uuid::new_v4()
-            )
+            vec![
+                ("method", method.into()),
+                ("path", path.into()),
+                ("status", status.into()),
+                ("error_type", err.into()),
+                ("latency_ms", ctx.latency.http.sample_slow(rng).into()),
+                ("request_id", random_uuid(rng).into()),
+            ]
         }
         1 => {
-            let svc = pick(rng, EXTERNAL_SERVICES);
+            let svc = ctx.external_service(rng);
             let err = pick(rng, ERROR_TYPES);
-            format!(
-                "external_service=\"{}\" error_type={} retries=3 last_attempt_ms={} circuit_state=OPEN fallback_used=true",
-                svc, err, random_latency(rng, LogLevel::Error)
-            )
+            vec![
+                ("external_service", svc.into()),
+                ("error_type", err.into()),
+                ("retries", 3.into()),
+                ("last_attempt_ms", ctx.latency.external.sample_slow(rng).into()),
+                ("circuit_state", "OPEN".into()),
+                ("fallback_used", true.into()),
+            ]
         }
         2 => {
-            let table = pick(rng, DB_TABLES);
+            let table = ctx.table(rng);
             let err = pick(rng, ERROR_TYPES);
-            format!(
-                "operation=WRITE table={} error_type={} connection_id={} statement_timeout_ms={} rollback=true",
-                table, err, rng.gen_range(1..1000), random_latency(rng, LogLevel::Error)
-            )
+            vec![
+                ("operation", "WRITE".into()),
+                ("table", table.into()),
+                ("error_type", err.into()),
+                ("connection_id", rng.gen_range(1..1000).into()),
+                ("statement_timeout_ms", ctx.latency.db.sample_slow(rng).into()),
+                ("rollback", true.into()),
+            ]
         }
         3 => {
-            let queue = pick(rng, QUEUE_NAMES);
+            let queue = ctx.queue(rng);
             let err = pick(rng, ERROR_TYPES);
-            format!(
-                "queue={} error_type={} message_id={} retry_count=3 dead_lettered=true original_timestamp={}",
-                queue, err, uuid::Uuid::new_v4(), chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
-            )
+            let original_timestamp = timestamp - Duration::seconds(rng.gen_range(60..3600));
+            vec![
+                ("queue", queue.into()),
+                ("error_type", err.into()),
+                ("message_id", random_uuid(rng).into()),
+                ("retry_count", 3.into()),
+                ("dead_lettered", true.into()),
+                ("original_timestamp", original_timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string().into()),
+            ]
         }
         4 => {
             let err = pick(rng, ERROR_TYPES);
-            let user_id = uuid::Uuid::new_v4();
-            format!(
-                "error_type={} user_id={} client_ip={} failure_count={} account_locked={}",
-                err, user_id, random_ip(rng), rng.gen_range(3..10), rng.gen_bool(0.3)
-            )
+            let user_id = random_uuid(rng);
+            vec![
+                ("error_type", err.into()),
+                ("user_id", user_id.into()),
+                ("client_ip", random_ip(rng).into()),
+                ("failure_count", rng.gen_range(3..10).into()),
+                ("account_locked", rng.gen_bool(0.3).into()),
+            ]
         }
         _ => {
             let err = pick(rng, ERROR_TYPES);
-            format!(
-                "error_type={} component={} heap_used_mb={} available_mb={} oom_killer_invoked={}",
-                err, pick(rng, &["worker", "scheduler", "gateway", "processor", "aggregator"]),
-                rng.gen_range(7000..8192), rng.gen_range(0..100), rng.gen_bool(0.2)
-            )
+            vec![
+                ("error_type", err.into()),
+                ("component", pick(rng, &["worker", "scheduler", "gateway", "processor", "aggregator"]).into()),
+                ("heap_used_mb", rng.gen_range(7000..8192).into()),
+                ("available_mb", rng.gen_range(0..100).into()),
+                ("oom_killer_invoked", rng.gen_bool(0.2).into()),
+            ]
         }
     };
 
-    if include_stack {
+    let stack = if include_stack {
         let num_frames = rng.gen_range(3..8);
-        let mut stack = String::from("\n  Stacktrace:\n");
-        for _ in 0..num_frames {
-            stack.push_str("    ");
-            stack.push_str(pick(rng, STACK_FRAMES));
-            stack.push('\n');
-        }
-        format!("{}{}", detail, stack)
+        Some((0..num_frames).map(|_| pick(rng, STACK_FRAMES)).collect())
     } else {
-        detail
+        None
+    };
+
+    (ERROR_CATEGORIES[category], detail, stack)
+}
+
+fn generate_fatal_detail(
+    rng: &mut impl Rng,
+    category: usize,
+    ctx: &ServiceContext,
+) -> (&'static str, Fields, Option<Vec<&'static str>>) {
+    let detail = match category {
+        0 => {
+            let components = &["http-server", "db-migrator", "config-loader", "grpc-server", "license-check"];
+            let reasons = &[
+                "address already in use", "migration checksum mismatch", "missing env DATABASE_URL",
+                "certificate file not found", "permission denied",
+            ];
+            vec![
+                ("component", pick(rng, components).into()),
+                ("reason", pick(rng, reasons).into()),
+                ("port", pick_u16(rng, &[8080, 8443, 9090, 5432, 6379]).into()),
+                ("exit_code", 1.into()),
+            ]
+        }
+        1 => {
+            vec![
+                ("heap_used_mb", rng.gen_range(8000..8192).into()),
+                ("heap_max_mb", 8192.into()),
+                ("rss_mb", rng.gen_range(8200..9000).into()),
+                ("exit_code", 137.into()),
+            ]
+        }
+        2 => {
+            vec![
+                ("table", ctx.table(rng).into()),
+                ("page", rng.gen_range(1..1000000).into()),
+                ("checksum_expected", format!("{:08x}", rng.gen::<u32>()).into()),
+                ("checksum_actual", format!("{:08x}", rng.gen::<u32>()).into()),
+                ("writes_halted", true.into()),
+            ]
+        }
+        _ => {
+            let panics = &[
+                "index out of bounds", "called unwrap on a None value",
+                "attempt to divide by zero", "assertion failed: state.is_valid()",
+            ];
+            vec![
+                ("worker", format!("worker-{}", rng.gen_range(1..128)).into()),
+                ("panic", pick(rng, panics).into()),
+                ("exit_code", 101.into()),
+            ]
+        }
+    };
+
+    // A process going down always dumps its stack
+    let num_frames = rng.gen_range(3..8);
+    let stack = (0..num_frames).map(|_| pick(rng, STACK_FRAMES)).collect();
+
+    (FATAL_CATEGORIES[category], detail, Some(stack))
+}
+
+/// Events generated ahead of their timestamp (trace spans, lifecycle end
+/// lines), released in timestamp order.
+pub struct EventQueue<'a> {
+    heap: BinaryHeap<Reverse<Queued<'a>>>,
+    seq: u64,
+}
+
+struct Queued<'a> {
+    seq: u64,
+    event: LogEvent<'a>,
+}
+
+impl Queued<'_> {
+    fn key(&self) -> (DateTime<Utc>, u64) {
+        (self.event.timestamp, self.seq)
+    }
+}
+
+impl PartialEq for Queued<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Queued<'_> {}
+
+impl PartialOrd for Queued<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
+impl Ord for Queued<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Ties keep insertion order so seeded runs stay reproducible
+        self.key().cmp(&other.key())
+    }
+}
+
+impl<'a> EventQueue<'a> {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            seq: 0,
+        }
+    }
+
+    pub fn push(&mut self, event: LogEvent<'a>) {
+        self.heap.push(Reverse(Queued { seq: self.seq, event }));
+        self.seq += 1;
+    }
+
+    /// The earliest queued event, however far ahead it is stamped.
+    pub fn pop(&mut self) -> Option<LogEvent<'a>> {
+        self.heap.pop().map(|Reverse(queued)| queued.event)
+    }
+
+    /// The earliest queued event stamped at or before `t`.
+    pub fn pop_due(&mut self, t: DateTime<Utc>) -> Option<LogEvent<'a>> {
+        if self.heap.peek()?.0.event.timestamp > t {
+            return None;
+        }
+        self.heap.pop().map(|Reverse(queued)| queued.event)
+    }
+}
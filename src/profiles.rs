@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::messages::INFO_CATEGORIES;
+
+/// What a service logs about: how often it emits each INFO category, extra
+/// domain messages, and the paths, tables, queues, cache keys, dependencies
+/// and batch jobs its lines name. Empty vocabulary lists fall back to the
+/// shared pools.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ServiceProfile {
+    /// Relative weights of the INFO categories (`http`, `db`, `cache`,
+    /// `queue`, `external`, `health`, `auth`, `batch`, `feature_flag`,
+    /// `metrics`). Categories left out are not logged; empty means all
+    /// categories equally.
+    pub categories: BTreeMap<String, u32>,
+    /// Extra INFO messages, keyed by the category whose detail fields they
+    /// carry, e.g. `{"external": ["Payment captured"]}`.
+    pub messages: BTreeMap<String, Vec<String>>,
+    pub paths: Vec<String>,
+    pub tables: Vec<String>,
+    pub queues: Vec<String>,
+    pub cache_keys: Vec<String>,
+    pub external_services: Vec<String>,
+    pub batch_jobs: Vec<String>,
+}
+
+impl ServiceProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        let known = |name: &String| INFO_CATEGORIES.contains(&name.as_str());
+        if let Some(unknown) = self.categories.keys().chain(self.messages.keys()).find(|name| !known(name)) {
+            return Err(format!("unknown category {}", unknown));
+        }
+        if !self.categories.is_empty() && self.categories.values().all(|&w| w == 0) {
+            return Err("category weights must not all be 0".into());
+        }
+        if self.categories.values().map(|&w| w as u64).sum::<u64>() > u32::MAX as u64 {
+            return Err("category weights are too large".into());
+        }
+        if self.messages.values().flatten().any(|m| m.trim().is_empty()) {
+            return Err("messages must not be empty".into());
+        }
+        Ok(())
+    }
+
+    /// The built-in profile of a default service name, or the generic
+    /// profile (every category, shared vocabulary) for any other name such as
+    /// `microservice-42`.
+    pub fn builtin(service_name: &str) -> Self {
+        let Some(builtin) = BUILTIN_PROFILES.iter().find(|p| p.name == service_name) else {
+            return Self::default();
+        };
+        let owned = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        let mut messages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (category, message) in builtin.messages {
+            messages.entry(category.to_string()).or_default().push(message.to_string());
+        }
+        Self {
+            categories: builtin.categories.iter().map(|&(c, w)| (c.to_string(), w)).collect(),
+            messages,
+            paths: owned(builtin.paths),
+            tables: owned(builtin.tables),
+            queues: owned(builtin.queues),
+            cache_keys: owned(builtin.cache_keys),
+            external_services: owned(builtin.external_services),
+            batch_jobs: owned(builtin.batch_jobs),
+        }
+    }
+}
+
+struct BuiltinProfile {
+    name: &'static str,
+    categories: &'static [(&'static str, u32)],
+    messages: &'static [(&'static str, &'static str)],
+    paths: &'static [&'static str],
+    tables: &'static [&'static str],
+    queues: &'static [&'static str],
+    cache_keys: &'static [&'static str],
+    external_services: &'static [&'static str],
+    batch_jobs: &'static [&'static str],
+}
+
+const SHARED: BuiltinProfile = BuiltinProfile {
+    name: "",
+    categories: &[],
+    messages: &[],
+    paths: &[],
+    tables: &[],
+    queues: &[],
+    cache_keys: &[],
+    external_services: &[],
+    batch_jobs: &[],
+};
+
+/// Profiles of the named default services.
+const BUILTIN_PROFILES: &[BuiltinProfile] = &[
+    BuiltinProfile {
+        name: "auth-service",
+        categories: &[("http", 30), ("auth", 40), ("db", 15), ("cache", 15), ("health", 5), ("metrics", 3), ("feature_flag", 2)],
+        messages: &[("auth", "MFA challenge verified"), ("auth", "Password hash upgraded")],
+        paths: &["/api/v1/auth/login", "/api/v1/auth/logout", "/api/v1/auth/refresh", "/api/v1/auth/verify", "/api/v1/auth/mfa", "/oauth/token"],
+        tables: &["users", "sessions", "roles", "permissions", "api_keys", "refresh_tokens"],
+        queues: &["auth-events", "session-revocations"],
+        cache_keys: &["session_token", "auth_permissions", "user_profile", "jwks"],
+        external_services: &["Auth0", "Vault", "Twilio", "PostgreSQL Primary", "Redis Cluster"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "user-service",
+        categories: &[("http", 35), ("db", 30), ("cache", 15), ("queue", 5), ("auth", 5), ("health", 5), ("metrics", 3), ("feature_flag", 2)],
+        messages: &[("db", "User profile updated"), ("queue", "User onboarding event emitted")],
+        paths: &["/api/v1/users", "/api/v2/users", "/api/v1/users/preferences", "/api/v1/users/avatar", "/api/v1/roles"],
+        tables: &["users", "user_profiles", "preferences", "addresses", "roles"],
+        queues: &["user-onboarding", "user-events"],
+        cache_keys: &["user_profile", "notification_prefs", "tenant_settings"],
+        external_services: &["PostgreSQL Primary", "PostgreSQL Replica", "Redis Cluster", "AWS S3"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "order-service",
+        categories: &[("http", 30), ("db", 25), ("queue", 20), ("external", 10), ("cache", 5), ("batch", 3), ("health", 4), ("metrics", 3)],
+        messages: &[("db", "Order created"), ("queue", "Order status change published")],
+        paths: &["/api/v1/orders", "/api/v2/orders", "/api/v1/cart", "/api/v1/checkout", "/api/v1/orders/returns"],
+        tables: &["orders", "order_items", "carts", "returns", "shipments"],
+        queues: &["order-processing", "order-events", "payment-requests"],
+        cache_keys: &["order_summary", "cart_contents", "price_matrix"],
+        external_services: &["PostgreSQL Primary", "Kafka Broker", "Redis Cluster"],
+        batch_jobs: &["abandoned-cart-cleanup", "order-reconciliation"],
+    },
+    BuiltinProfile {
+        name: "payment-service",
+        categories: &[("http", 25), ("external", 25), ("db", 25), ("queue", 10), ("auth", 3), ("cache", 3), ("health", 5), ("metrics", 4)],
+        messages: &[("external", "Payment authorized"), ("external", "Payment captured"), ("db", "Refund recorded")],
+        paths: &["/api/v1/payments", "/api/v1/payments/refunds", "/api/v1/payment-methods", "/api/v3/transactions", "/api/v1/payments/webhooks"],
+        tables: &["payments", "transactions", "refunds", "payment_methods", "ledger_entries"],
+        queues: &["payment-webhooks", "payment-requests", "refund-processing"],
+        cache_keys: &["payment_method_tokens", "fx_rates", "fraud_scores"],
+        external_services: &["Stripe API", "Adyen API", "PayPal API", "Vault", "PostgreSQL Primary"],
+        batch_jobs: &["nightly-reconciliation", "settlement-report"],
+    },
+    BuiltinProfile {
+        name: "inventory-service",
+        categories: &[("http", 25), ("db", 35), ("cache", 15), ("queue", 15), ("batch", 3), ("health", 4), ("metrics", 3)],
+        messages: &[("db", "Stock reserved"), ("db", "Stock level adjusted")],
+        paths: &["/api/v1/inventory", "/api/v1/inventory/reservations", "/api/v1/warehouses", "/api/v1/stock-levels"],
+        tables: &["inventory", "stock_levels", "reservations", "warehouses", "skus"],
+        queues: &["inventory-sync", "stock-reservations"],
+        cache_keys: &["inventory_count", "warehouse_locations", "sku_lookup"],
+        external_services: &["PostgreSQL Primary", "Redis Cluster", "Kafka Broker"],
+        batch_jobs: &["stock-recount", "reservation-expiry"],
+    },
+    BuiltinProfile {
+        name: "notification-service",
+        categories: &[("queue", 35), ("external", 30), ("db", 10), ("http", 10), ("cache", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("queue", "Email notification queued"), ("external", "Push notification sent"), ("external", "SMS message delivered")],
+        paths: &["/api/v1/notifications", "/api/v1/notifications/preferences", "/api/v1/devices"],
+        tables: &["notifications", "devices", "notification_templates", "delivery_attempts"],
+        queues: &["push-notifications", "sms-notifications", "email-notifications"],
+        cache_keys: &["notification_prefs", "device_tokens", "template_cache"],
+        external_services: &["Twilio", "SendGrid", "Firebase Cloud Messaging", "Apple Push Notification Service"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "search-service",
+        categories: &[("http", 40), ("cache", 20), ("external", 20), ("batch", 8), ("db", 2), ("health", 5), ("metrics", 5)],
+        messages: &[("batch", "Search index updated"), ("http", "Search query served")],
+        paths: &["/api/v2/search", "/api/v2/search/suggest", "/api/v2/search/facets", "/api/v1/catalog/search"],
+        tables: &["search_synonyms", "search_queries"],
+        queues: &["search-indexing"],
+        cache_keys: &["search_results", "query_suggestions", "facet_counts"],
+        external_services: &["Elasticsearch", "OpenSearch", "Redis Cluster"],
+        batch_jobs: &["search-reindex", "synonym-refresh"],
+    },
+    BuiltinProfile {
+        name: "analytics-service",
+        categories: &[("queue", 30), ("db", 25), ("batch", 20), ("external", 10), ("http", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("queue", "Event batch ingested"), ("batch", "Aggregation window closed"), ("batch", "Data export completed")],
+        paths: &["/api/v2/analytics", "/api/v2/analytics/events", "/api/v2/analytics/funnels", "/api/v2/dashboards"],
+        tables: &["events", "page_views", "sessions_daily", "funnels", "metrics"],
+        queues: &["analytics-events", "etl-pipeline", "clickstream"],
+        cache_keys: &["dashboard_data", "funnel_results"],
+        external_services: &["Kafka Broker", "Snowflake", "AWS S3", "BigQuery"],
+        batch_jobs: &["usage-aggregation", "daily-rollup", "funnel-recompute"],
+    },
+    BuiltinProfile {
+        name: "gateway-service",
+        categories: &[("http", 55), ("auth", 15), ("cache", 10), ("external", 5), ("health", 5), ("metrics", 5), ("feature_flag", 5)],
+        messages: &[("http", "Request routed to upstream"), ("http", "Upstream response proxied")],
+        tables: &["api_keys", "rate_limits", "routes"],
+        queues: &["audit-trail"],
+        cache_keys: &["rate_limit_counter", "api_response", "route_table", "auth_permissions"],
+        external_services: &["Auth0", "Cloudflare", "Fastly CDN", "Consul"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "billing-service",
+        categories: &[("db", 30), ("batch", 20), ("external", 15), ("http", 15), ("queue", 10), ("health", 5), ("metrics", 5)],
+        messages: &[("batch", "Invoice generated"), ("db", "Subscription renewed"), ("db", "Usage record aggregated")],
+        paths: &["/api/v1/billing", "/api/v1/invoices", "/api/v1/subscriptions", "/api/v1/coupons", "/api/v1/usage"],
+        tables: &["invoices", "subscriptions", "invoice_lines", "usage_records", "coupons", "accounts"],
+        queues: &["invoice-generation", "subscription-events", "payment-requests"],
+        cache_keys: &["price_matrix", "plan_catalog", "tax_rates"],
+        external_services: &["Stripe API", "Avalara API", "PostgreSQL Primary"],
+        batch_jobs: &["invoice-generation", "subscription-renewal", "usage-aggregation", "dunning-run"],
+    },
+    BuiltinProfile {
+        name: "shipping-service",
+        categories: &[("http", 25), ("external", 25), ("db", 25), ("queue", 15), ("health", 5), ("metrics", 5)],
+        messages: &[("external", "Shipping label created"), ("external", "Tracking update received")],
+        paths: &["/api/v1/shipments", "/api/v1/shipments/tracking", "/api/v1/shipping/rates", "/api/v1/labels"],
+        tables: &["shipments", "tracking_events", "carriers", "shipping_rates"],
+        queues: &["shipment-updates", "label-generation"],
+        cache_keys: &["shipping_rates", "carrier_status", "geo_lookup"],
+        external_services: &["FedEx API", "UPS API", "DHL API", "Google Maps API"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "catalog-service",
+        categories: &[("http", 40), ("db", 25), ("cache", 25), ("batch", 3), ("health", 4), ("metrics", 3)],
+        messages: &[("db", "Product updated"), ("batch", "Search index updated")],
+        paths: &["/api/v1/catalog", "/api/v1/products", "/api/v2/products", "/api/v1/categories", "/api/v1/tags"],
+        tables: &["products", "categories", "tags", "product_variants", "prices"],
+        queues: &["catalog-updates", "search-indexing"],
+        cache_keys: &["product_catalog", "price_matrix", "category_tree"],
+        external_services: &["PostgreSQL Replica", "Redis Cluster", "AWS S3", "Elasticsearch"],
+        batch_jobs: &["catalog-sync", "price-refresh"],
+    },
+    BuiltinProfile {
+        name: "review-service",
+        categories: &[("http", 40), ("db", 30), ("cache", 10), ("queue", 10), ("health", 5), ("metrics", 5)],
+        messages: &[("db", "Review submitted"), ("queue", "Review sent to moderation")],
+        paths: &["/api/v1/reviews", "/api/v1/ratings", "/api/v1/comments"],
+        tables: &["reviews", "ratings", "comments", "moderation_queue"],
+        queues: &["review-moderation", "search-indexing"],
+        cache_keys: &["review_summary", "rating_aggregates"],
+        external_services: &["Perspective API", "PostgreSQL Primary", "Redis Cluster"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "recommendation-service",
+        categories: &[("http", 35), ("cache", 25), ("batch", 15), ("db", 10), ("external", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("http", "Recommendations served"), ("batch", "Model snapshot loaded")],
+        paths: &["/api/v1/recommendations", "/api/v1/recommendations/similar", "/api/v1/feeds"],
+        tables: &["recommendations", "user_embeddings", "item_embeddings"],
+        queues: &["recommendation-training", "analytics-events"],
+        cache_keys: &["recommendations", "user_embeddings", "trending_items"],
+        external_services: &["Redis Cluster", "AWS SageMaker", "Feature Store"],
+        batch_jobs: &["model-retraining", "embedding-refresh"],
+    },
+    BuiltinProfile {
+        name: "email-service",
+        categories: &[("queue", 40), ("external", 35), ("db", 10), ("http", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("queue", "Email notification queued"), ("external", "Email delivered"), ("external", "Bounce notification processed")],
+        paths: &["/api/v1/emails", "/api/v1/emails/templates", "/api/v1/emails/bounces"],
+        tables: &["emails", "email_templates", "bounces", "suppressions"],
+        queues: &["email-notifications", "email-bounces"],
+        cache_keys: &["template_cache", "suppression_list"],
+        external_services: &["SendGrid", "Mailgun", "AWS SES"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "scheduler-service",
+        categories: &[("batch", 50), ("db", 15), ("queue", 15), ("health", 10), ("http", 5), ("metrics", 5)],
+        messages: &[("batch", "Cron trigger fired"), ("batch", "Job run recorded")],
+        paths: &["/api/v1/schedules", "/api/v1/jobs"],
+        tables: &["scheduled_jobs", "job_runs", "job_queue"],
+        queues: &["batch-processing", "job-triggers"],
+        cache_keys: &["job_locks"],
+        external_services: &["Consul", "PostgreSQL Primary"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "config-service",
+        categories: &[("http", 30), ("cache", 25), ("db", 15), ("feature_flag", 15), ("external", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("cache", "Configuration snapshot published"), ("feature_flag", "Feature flag rule updated")],
+        paths: &["/api/internal/config", "/api/v1/config", "/api/v1/feature-flags"],
+        tables: &["configurations", "feature_flags", "config_versions"],
+        queues: &["config-changes"],
+        cache_keys: &["config_snapshot", "feature_flags", "tenant_settings"],
+        external_services: &["Consul", "Vault", "LaunchDarkly"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "audit-service",
+        categories: &[("queue", 40), ("db", 40), ("batch", 5), ("http", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("db", "Audit record written"), ("batch", "Audit log segment archived")],
+        paths: &["/api/v1/audit", "/api/v1/audit/export"],
+        tables: &["audit_log", "audit_archive", "access_events"],
+        queues: &["audit-trail"],
+        external_services: &["AWS S3", "Elasticsearch"],
+        batch_jobs: &["audit-archival", "data-retention-cleanup"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "report-service",
+        categories: &[("batch", 35), ("db", 30), ("http", 15), ("external", 10), ("health", 5), ("metrics", 5)],
+        messages: &[("batch", "PDF report generated"), ("batch", "Data export completed")],
+        paths: &["/api/v2/reports", "/api/v2/reports/schedules", "/api/v1/exports"],
+        tables: &["reports", "report_schedules", "report_runs"],
+        queues: &["report-generation", "pdf-generation"],
+        cache_keys: &["report_cache", "dashboard_data"],
+        external_services: &["AWS S3", "Snowflake", "SendGrid"],
+        batch_jobs: &["report-export", "scheduled-reports"],
+    },
+    BuiltinProfile {
+        name: "cache-service",
+        categories: &[("cache", 60), ("http", 15), ("health", 10), ("metrics", 10), ("external", 5)],
+        messages: &[("cache", "Cache entry evicted"), ("cache", "Cache invalidation broadcast")],
+        paths: &["/api/internal/cache", "/api/internal/cache/invalidate"],
+        tables: &["cache_entries"],
+        queues: &["cache-invalidations"],
+        external_services: &["Redis Cluster", "Memcached"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "media-service",
+        categories: &[("http", 35), ("external", 25), ("queue", 15), ("db", 10), ("cache", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("http", "File upload completed"), ("queue", "Thumbnail generated")],
+        paths: &["/api/v1/uploads", "/api/v1/media", "/api/v1/attachments", "/api/v1/downloads"],
+        tables: &["media_assets", "attachments", "thumbnails"],
+        queues: &["image-processing", "video-transcoding"],
+        cache_keys: &["media_metadata", "signed_urls"],
+        external_services: &["AWS S3", "Cloudflare", "Fastly CDN", "AWS MediaConvert"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "webhook-service",
+        categories: &[("external", 40), ("queue", 30), ("db", 15), ("http", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("external", "Webhook delivered successfully"), ("queue", "Webhook delivery scheduled for retry")],
+        paths: &["/api/v3/webhooks", "/api/v3/webhooks/deliveries"],
+        tables: &["webhook_endpoints", "webhook_deliveries"],
+        queues: &["webhook-deliveries", "payment-webhooks", "webhook-retries"],
+        cache_keys: &["endpoint_secrets"],
+        external_services: &["Slack Webhook", "GitHub API", "Stripe API", "Customer Endpoint"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "export-service",
+        categories: &[("batch", 40), ("db", 25), ("external", 15), ("queue", 10), ("health", 5), ("metrics", 5)],
+        messages: &[("batch", "Data export completed"), ("batch", "PDF report generated")],
+        paths: &["/api/v1/exports", "/api/v1/downloads"],
+        tables: &["exports", "export_jobs"],
+        queues: &["data-export"],
+        cache_keys: &["export_status"],
+        external_services: &["AWS S3", "Snowflake"],
+        batch_jobs: &["report-export", "data-export", "gdpr-export"],
+    },
+    BuiltinProfile {
+        name: "import-service",
+        categories: &[("batch", 35), ("db", 30), ("queue", 15), ("external", 10), ("http", 5), ("health", 5)],
+        messages: &[("http", "File upload completed"), ("batch", "Import file validated")],
+        paths: &["/api/v1/imports", "/api/v1/uploads"],
+        tables: &["imports", "import_rows", "import_errors"],
+        queues: &["etl-pipeline", "import-validation"],
+        cache_keys: &["import_status"],
+        external_services: &["AWS S3", "SFTP Gateway"],
+        batch_jobs: &["csv-import", "catalog-sync"],
+    },
+    BuiltinProfile {
+        name: "monitoring-service",
+        categories: &[("health", 35), ("metrics", 30), ("external", 20), ("http", 10), ("db", 5)],
+        messages: &[("external", "Alert notification sent"), ("metrics", "Scrape target collected")],
+        paths: &["/api/internal/metrics", "/api/internal/health", "/api/v1/alerts"],
+        tables: &["alerts", "alert_rules", "incidents"],
+        queues: &["alert-notifications"],
+        cache_keys: &["alert_state"],
+        external_services: &["Datadog", "PagerDuty", "Prometheus", "New Relic", "Sentry"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "logging-service",
+        categories: &[("queue", 40), ("metrics", 20), ("external", 20), ("health", 10), ("batch", 10)],
+        messages: &[("queue", "Log batch shipped"), ("batch", "Index rolled over")],
+        paths: &["/api/internal/logs", "/api/internal/metrics"],
+        tables: &["log_indices", "retention_policies"],
+        queues: &["log-ingest", "audit-trail"],
+        cache_keys: &["index_templates"],
+        external_services: &["Elasticsearch", "AWS S3", "Kafka Broker"],
+        batch_jobs: &["index-rollover", "data-retention-cleanup"],
+    },
+    BuiltinProfile {
+        name: "discovery-service",
+        categories: &[("health", 35), ("http", 30), ("cache", 15), ("external", 10), ("metrics", 10)],
+        messages: &[("health", "Stale instance deregistered"), ("health", "Heartbeat received")],
+        paths: &["/api/internal/registry", "/api/internal/health"],
+        tables: &["service_instances"],
+        queues: &["registry-events"],
+        cache_keys: &["service_registry"],
+        external_services: &["Consul", "etcd", "Kubernetes API"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "rate-limiter-service",
+        categories: &[("http", 40), ("cache", 40), ("health", 10), ("metrics", 10)],
+        messages: &[("cache", "Token bucket refilled"), ("http", "Quota usage reported")],
+        paths: &["/api/internal/ratelimit/check", "/api/v1/quotas"],
+        tables: &["rate_limits", "quotas"],
+        queues: &["quota-events"],
+        cache_keys: &["rate_limit_counter", "quota_config"],
+        external_services: &["Redis Cluster"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "session-service",
+        categories: &[("auth", 35), ("cache", 35), ("http", 15), ("db", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("auth", "Session created"), ("auth", "Session expired")],
+        paths: &["/api/v1/sessions", "/api/v1/auth/refresh", "/api/v1/auth/verify"],
+        tables: &["sessions", "refresh_tokens"],
+        queues: &["session-revocations"],
+        cache_keys: &["session_token", "user_profile"],
+        external_services: &["Redis Cluster", "Auth0"],
+        ..SHARED
+    },
+    BuiltinProfile {
+        name: "tenant-service",
+        categories: &[("http", 30), ("db", 30), ("cache", 15), ("auth", 10), ("feature_flag", 5), ("health", 5), ("metrics", 5)],
+        messages: &[("db", "Tenant provisioned"), ("db", "Tenant plan changed")],
+        paths: &["/api/v1/tenants", "/api/v1/tenants/settings", "/api/v1/tenants/members"],
+        tables: &["tenants", "tenant_settings", "tenant_members", "plans"],
+        queues: &["tenant-provisioning"],
+        cache_keys: &["tenant_settings", "plan_catalog"],
+        external_services: &["PostgreSQL Primary", "Auth0", "Stripe API"],
+        ..SHARED
+    },
+];
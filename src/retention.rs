@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+
+use crate::events::{JobEvent, JobEventLog};
+
+/// Limits on the rotated archives a service keeps. Unset limits do not
+/// apply; with none set, archives are kept forever.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Keep at most this many archives.
+    pub max_files: Option<u32>,
+    /// Delete archives rotated longer ago than this, on the service's clock.
+    pub max_age_seconds: Option<u64>,
+    /// Keep the archives' total size on disk at or below this.
+    pub max_total_size_mb: Option<u64>,
+}
+
+/// Upper bound on `max_age_seconds` (100 years); longer ages would never
+/// expire anything anyway.
+const MAX_AGE_SECONDS: u64 = 100 * 365 * 24 * 3600;
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_age_seconds == Some(0) {
+            return Err("retention.max_age_seconds must be greater than 0".into());
+        }
+        if self.max_age_seconds.is_some_and(|s| s > MAX_AGE_SECONDS) {
+            return Err(format!("retention.max_age_seconds must be at most {}", MAX_AGE_SECONDS));
+        }
+        if self.max_total_size_mb == Some(0) {
+            return Err("retention.max_total_size_mb must be greater than 0".into());
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_files.is_some() || self.max_age_seconds.is_some() || self.max_total_size_mb.is_some()
+    }
+
+    fn max_age(&self) -> Option<TimeDelta> {
+        self.max_age_seconds
+            .map(|s| i64::try_from(s).ok().and_then(TimeDelta::try_seconds).unwrap_or(TimeDelta::MAX))
+    }
+}
+
+/// A rotated file that is still on disk.
+struct Archive {
+    name: String,
+    /// Where the archive ends up once any compression has finished.
+    path: PathBuf,
+    /// Where it is until then.
+    plain_path: PathBuf,
+    rotated_at: DateTime<Utc>,
+}
+
+/// Applies a service's retention policy to its archives, oldest first, and
+/// records every deletion in the job event log.
+pub struct Retention {
+    policy: RetentionPolicy,
+    service: String,
+    events: Arc<JobEventLog>,
+    disk_bytes: Arc<AtomicU64>,
+    archives: VecDeque<Archive>,
+}
+
+impl Retention {
+    pub fn new(policy: RetentionPolicy, service: &str, events: Arc<JobEventLog>, disk_bytes: Arc<AtomicU64>) -> Self {
+        Self {
+            policy,
+            service: service.to_string(),
+            events,
+            disk_bytes,
+            archives: VecDeque::new(),
+        }
+    }
+
+    /// Track an archive rotated at `rotated_at`, currently at `plain_path` and
+    /// finally named `name` at `path`.
+    pub fn archived(&mut self, name: String, path: PathBuf, plain_path: PathBuf, rotated_at: DateTime<Utc>) {
+        self.archives.push_back(Archive {
+            name,
+            path,
+            plain_path,
+            rotated_at,
+        });
+    }
+
+    /// Follow archives renamed on disk, e.g. shifted from `app.log.1` to
+    /// `app.log.2`. `rename` maps a current path to its new one.
+    pub fn renamed(&mut self, rename: impl Fn(&Path) -> Option<PathBuf>) {
+        for archive in &mut self.archives {
+            if let Some(path) = rename(&archive.path) {
+                archive.name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                archive.path = path;
+            }
+            if let Some(plain_path) = rename(&archive.plain_path) {
+                archive.plain_path = plain_path;
+            }
+        }
+    }
+
+    /// Whether the oldest archive has outlived the maximum age at `now`. Cheap
+    /// enough to check on every line.
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        match (self.policy.max_age(), self.archives.front()) {
+            (Some(max_age), Some(oldest)) => now - oldest.rotated_at > max_age,
+            _ => false,
+        }
+    }
+
+    /// Delete the oldest archives until every limit holds at `now`.
+    pub fn enforce(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        while let Some(reason) = self.violated(now)? {
+            let oldest = &self.archives[0];
+            // Archives are only deleted once compressed, so a deletion never
+            // races the compression worker
+            let bytes = match fs::metadata(&oldest.path) {
+                Ok(metadata) => metadata.len(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    if oldest.plain_path.exists() {
+                        break;
+                    }
+                    // Already removed by someone else
+                    self.archives.pop_front();
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            fs::remove_file(&oldest.path)?;
+            self.disk_bytes.fetch_sub(bytes, Ordering::Relaxed);
+            self.events.record(&JobEvent {
+                timestamp: now,
+                service: &self.service,
+                event: "archive_deleted",
+                file: &oldest.name,
+                bytes,
+                reason,
+            })?;
+            self.archives.pop_front();
+        }
+        Ok(())
+    }
+
+    /// The first limit the current archives break, if any.
+    fn violated(&self, now: DateTime<Utc>) -> io::Result<Option<&'static str>> {
+        if self.archives.is_empty() {
+            return Ok(None);
+        }
+        if self.policy.max_files.is_some_and(|max| self.archives.len() > max as usize) {
+            return Ok(Some("max_files"));
+        }
+        if self.expired(now) {
+            return Ok(Some("max_age"));
+        }
+        if let Some(max_mb) = self.policy.max_total_size_mb {
+            let mut total = 0;
+            for archive in &self.archives {
+                total += match fs::metadata(&archive.path).or_else(|_| fs::metadata(&archive.plain_path)) {
+                    Ok(metadata) => metadata.len(),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+                    Err(e) => return Err(e),
+                };
+            }
+            if total > max_mb.saturating_mul(1024 * 1024) {
+                return Ok(Some("max_total_size"));
+            }
+        }
+        Ok(None)
+    }
+}
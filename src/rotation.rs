@@ -1,53 +1,209 @@
-use std::fs::{self, File, rename};
+use std::fs::{self, File, OpenOptions, rename};
 use std::io::{self, Write, BufWriter};
 use std::path::{Path, PathBuf};
 
-use chrono::Utc;
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use clap::ValueEnum;
+use serde::Deserialize;
 
-/// A writer that automatically rotates log files when they exceed a size limit.
+use crate::compression::Compressor;
+use crate::labels::{Anomaly, LabelSidecar};
+use crate::manifest::Manifest;
+use crate::retention::Retention;
+
+/// When a service's active log file is archived and a new one started.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RotationPolicy {
+    /// When the file reaches its maximum size.
+    #[default]
+    Size,
+    /// When a line falls into a new period (UTC) on the service's clock.
+    Time {
+        #[serde(default)]
+        interval: RotationInterval,
+    },
+    /// Whichever comes first; a period can span several numbered files.
+    SizeOrTime {
+        #[serde(default)]
+        interval: RotationInterval,
+    },
+}
+
+/// Calendar period of a time-based rotation policy.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationInterval {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl RotationInterval {
+    fn period_start(self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let length = match self {
+            RotationInterval::Minutely => TimeDelta::minutes(1),
+            RotationInterval::Hourly => TimeDelta::hours(1),
+            RotationInterval::Daily => TimeDelta::days(1),
+        };
+        time.duration_trunc(length).unwrap_or(time)
+    }
+
+    /// Period part of an archive name, like Logback's `%d{yyyy-MM-dd-HH}`.
+    fn period_format(self) -> &'static str {
+        match self {
+            RotationInterval::Minutely => "%Y-%m-%d-%H-%M",
+            RotationInterval::Hourly => "%Y-%m-%d-%H",
+            RotationInterval::Daily => "%Y-%m-%d",
+        }
+    }
+}
+
+/// Name of the active file under the stable-name rotation strategies.
+pub const ACTIVE_FILE_NAME: &str = "app.log";
+
+/// How the active file is turned into an archive, which decides what an
+/// agent tailing the service directory sees.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationStrategy {
+    /// The active file is named after its start time and renamed to its
+    /// archive name, so every file has a new name and inode.
+    #[default]
+    Timestamped,
+    /// `app.log` is renamed to its archive name and recreated: the path stays
+    /// the same but gets a new inode, like logrotate's `create`.
+    Create,
+    /// `app.log` is copied to its archive name and truncated in place: path
+    /// and inode stay the same and the file shrinks, like logrotate's
+    /// `copytruncate`.
+    Copytruncate,
+    /// `app.log` is recreated after older archives shift up a number
+    /// (`app.log.1` to `app.log.2`, ...) and it is renamed to `app.log.1`,
+    /// like logrotate's default. Archive names ignore the rotation policy.
+    Numbered,
+}
+
+impl RotationPolicy {
+    fn interval(self) -> Option<RotationInterval> {
+        match self {
+            RotationPolicy::Size => None,
+            RotationPolicy::Time { interval } | RotationPolicy::SizeOrTime { interval } => Some(interval),
+        }
+    }
+}
+
+/// A writer that automatically rotates log files when they exceed a size
+/// limit, cross into a new time period, or either, depending on its policy.
 pub struct RotatingWriter {
     dir: PathBuf,
     max_bytes: u64,
+    policy: RotationPolicy,
+    strategy: RotationStrategy,
+    /// Highest archive number in use (numbered strategy only).
+    numbered: u32,
+    /// Start of the period the current file's lines belong to (time-based
+    /// policies only), and the file's number within that period.
+    period: Option<DateTime<Utc>>,
+    period_index: u32,
     current_bytes: u64,
+    current_lines: u64,
+    /// Byte offset and 1-based line number where the last line started.
+    last_line_start: (u64, u64),
     writer: BufWriter<File>,
     current_path: PathBuf,
     file_index: u32,
+    labels: LabelSidecar,
+    manifest: Manifest,
+    /// Compresses archives in the background, if enabled.
+    compressor: Option<Compressor>,
+    /// Deletes old archives, if enabled.
+    retention: Option<Retention>,
 }
 
 impl RotatingWriter {
-    /// Create a new RotatingWriter that writes into `dir` and rotates at `max_bytes`.
-    pub fn new(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+    /// Create a new RotatingWriter that writes into `dir` and rotates at
+    /// `max_bytes` and/or period boundaries according to `policy`, archiving
+    /// files by `strategy` and handing archives to `compressor` and
+    /// `retention` if given. File names are derived from `now`, the
+    /// (possibly simulated) current time.
+    pub fn new(
+        dir: &Path,
+        max_bytes: u64,
+        policy: RotationPolicy,
+        strategy: RotationStrategy,
+        compressor: Option<Compressor>,
+        retention: Option<Retention>,
+        now: DateTime<Utc>,
+    ) -> io::Result<Self> {
         fs::create_dir_all(dir)?;
 
-        let file_name = format!("{}.log", Utc::now().format("%Y-%m-%d_%H-%M-%S"));
+        let file_name = match strategy {
+            RotationStrategy::Timestamped => format!("{}.log", now.format("%Y-%m-%d_%H-%M-%S")),
+            _ => ACTIVE_FILE_NAME.to_string(),
+        };
         let path = dir.join(&file_name);
-        let file = File::create(&path)?;
+        let file = create_active(&path)?;
 
         Ok(Self {
             dir: dir.to_path_buf(),
             max_bytes,
+            policy,
+            strategy,
+            numbered: 0,
+            period: policy.interval().map(|interval| interval.period_start(now)),
+            period_index: 0,
             current_bytes: 0,
+            current_lines: 0,
+            last_line_start: (0, 1),
             writer: BufWriter::with_capacity(64 * 1024, file),
             current_path: path,
             file_index: 0,
+            labels: LabelSidecar::new(dir, strategy == RotationStrategy::Numbered),
+            manifest: Manifest::new(dir),
+            compressor,
+            retention,
         })
     }
 
-    /// Write a log line. Rotates the file if the size limit is exceeded.
-    /// Returns the number of bytes written.
-    pub fn write_line(&mut self, line: &str) -> io::Result<usize> {
+    /// Write a log line stamped at `now`. Rotates the file if the size limit
+    /// is exceeded. Returns the number of bytes written.
+    pub fn write_line(&mut self, line: &str, now: DateTime<Utc>) -> io::Result<usize> {
         let bytes = line.as_bytes();
         let newline = b"\n";
         let total = bytes.len() + newline.len();
 
+        if let Some(retention) = &mut self.retention {
+            if retention.expired(now) {
+                retention.enforce(now)?;
+            }
+        }
+
         // Check if we need to rotate before writing
-        if self.current_bytes + total as u64 > self.max_bytes && self.current_bytes > 0 {
-            self.rotate()?;
+        let period = self.policy.interval().map(|interval| interval.period_start(now));
+        let new_period = period > self.period;
+        let full = !matches!(self.policy, RotationPolicy::Time { .. }) && self.current_bytes + total as u64 > self.max_bytes;
+        if self.current_bytes == 0 {
+            // An empty file takes the period of its first line
+            self.period = period;
+        } else if new_period || full {
+            self.rotate(now)?;
+            if new_period {
+                self.period = period;
+                self.period_index = 0;
+            } else {
+                self.period_index += 1;
+            }
         }
 
         self.writer.write_all(bytes)?;
         self.writer.write_all(newline)?;
+        self.manifest.line(bytes, now);
+        self.last_line_start = (self.current_bytes, self.current_lines + 1);
         self.current_bytes += total as u64;
+        // Multiline records (e.g. stack traces) span several physical lines
+        self.current_lines += 1 + bytes.iter().filter(|&&b| b == b'\n').count() as u64;
 
         // Flush periodically (every ~256KB) to avoid losing too much on crash
         if self.current_bytes % (256 * 1024) < total as u64 {
@@ -57,33 +213,181 @@ impl RotatingWriter {
         Ok(total)
     }
 
-    fn rotate(&mut self) -> io::Result<()> {
-        // Flush and drop the current writer
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
         self.writer.flush()?;
-
-        // Rename current file to archived name
         self.file_index += 1;
-        let archived_name = format!(
-            "{}_{:04}.log",
-            Utc::now().format("%Y-%m-%d_%H-%M-%S"),
-            self.file_index
-        );
+
+        // Move the current file's content to its archive name
+        let archived_name = match self.strategy {
+            RotationStrategy::Numbered => {
+                self.shift_numbered()?;
+                format!("{}.1", ACTIVE_FILE_NAME)
+            }
+            _ => self.archive_name(now),
+        };
         let archived_path = self.dir.join(&archived_name);
-        rename(&self.current_path, &archived_path)?;
-
-        // Open new file
-        let new_name = format!("{}.log", Utc::now().format("%Y-%m-%d_%H-%M-%S%.f"));
-        let new_path = self.dir.join(&new_name);
-        let file = File::create(&new_path)?;
-        self.writer = BufWriter::with_capacity(64 * 1024, file);
-        self.current_path = new_path;
+        if self.strategy == RotationStrategy::Copytruncate {
+            // Synchronous, so no line can land between the copy and the
+            // truncation
+            fs::copy(&self.current_path, &archived_path)?;
+            self.writer.get_ref().set_len(0)?;
+        } else {
+            rename(&self.current_path, &archived_path)?;
+        }
+
+        let final_name = match &self.compressor {
+            Some(compressor) => compressor.final_name(&archived_name),
+            None => archived_name.clone(),
+        };
+        self.labels.commit(&final_name, &archived_name)?;
+        self.manifest.closed(final_name.clone(), archived_name, self.current_lines, self.current_bytes);
+        if let Some(retention) = &mut self.retention {
+            retention.archived(final_name.clone(), self.dir.join(&final_name), archived_path.clone(), now);
+        }
+        // Numbered archives are renamed on every rotation, so the delayed one
+        // is found by its number rather than remembered by the compressor
+        let delayed = self.numbered_path(2, false);
+        if let Some(compressor) = &mut self.compressor {
+            if self.strategy != RotationStrategy::Numbered {
+                compressor.archived(archived_path);
+            } else if !compressor.delays() {
+                compressor.compress(archived_path);
+            } else if delayed.exists() {
+                compressor.compress(delayed);
+            }
+        }
+        if let Some(retention) = &mut self.retention {
+            retention.enforce(now)?;
+        }
+        self.manifest.save(false)?;
+
+        // Start the next active file; copytruncate keeps writing to the
+        // emptied one
+        if self.strategy != RotationStrategy::Copytruncate {
+            let new_path = match self.strategy {
+                RotationStrategy::Timestamped => self.dir.join(format!("{}.log", now.format("%Y-%m-%d_%H-%M-%S%.f"))),
+                _ => self.dir.join(ACTIVE_FILE_NAME),
+            };
+            self.writer = BufWriter::with_capacity(64 * 1024, create_active(&new_path)?);
+            self.current_path = new_path;
+        }
         self.current_bytes = 0;
+        self.current_lines = 0;
 
         Ok(())
     }
 
+    /// Archive name of the current file under the rotation policy.
+    fn archive_name(&self, now: DateTime<Utc>) -> String {
+        match (self.policy, self.period) {
+            (RotationPolicy::Time { interval }, Some(period)) => {
+                format!("{}.log", period.format(interval.period_format()))
+            }
+            (RotationPolicy::SizeOrTime { interval }, Some(period)) => {
+                format!("{}.{}.log", period.format(interval.period_format()), self.period_index)
+            }
+            _ => format!("{}_{:04}.log", now.format("%Y-%m-%d_%H-%M-%S"), self.file_index),
+        }
+    }
+
+    /// Path of numbered archive `index`, as written or once compressed.
+    fn numbered_path(&self, index: u32, compressed: bool) -> PathBuf {
+        let name = format!("{}.{}", ACTIVE_FILE_NAME, index);
+        match (&self.compressor, compressed) {
+            (Some(compressor), true) => self.dir.join(compressor.final_name(&name)),
+            _ => self.dir.join(name),
+        }
+    }
+
+    /// Free `app.log.1` by renaming every numbered archive to the next
+    /// number, highest first.
+    fn shift_numbered(&mut self) -> io::Result<()> {
+        // The compression worker must not be holding an archive we rename
+        if let Some(compressor) = &self.compressor {
+            compressor.wait_idle();
+        }
+        // Forget numbers retention has emptied, so the work stays bounded
+        while self.numbered > 0
+            && !self.numbered_path(self.numbered, false).exists()
+            && !self.numbered_path(self.numbered, true).exists()
+        {
+            self.numbered -= 1;
+        }
+        for index in (1..=self.numbered).rev() {
+            for compressed in [true, false] {
+                let from = self.numbered_path(index, compressed);
+                match rename(&from, self.numbered_path(index + 1, compressed)) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        self.numbered += 1;
+        if let Some(retention) = &mut self.retention {
+            retention.renamed(shifted_path);
+        }
+        self.manifest.renamed(shifted_path);
+        self.labels.renamed(shifted_path)?;
+        Ok(())
+    }
+
+    /// Record a ground-truth label for the line written last.
+    pub fn label_last_line(&mut self, timestamp: DateTime<Utc>, anomaly: Anomaly) {
+        let (offset, line) = self.last_line_start;
+        self.labels.push(offset, line, timestamp, anomaly);
+    }
+
     pub fn flush(&mut self) -> io::Result<()> {
         self.writer.flush()
     }
+
+    /// Flush the current file, write out its remaining labels, wait for
+    /// pending archives to be compressed, apply retention one last time at
+    /// `now`, and complete the labels and the manifest.
+    pub fn finish(mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.writer.flush()?;
+        let file_name = self.current_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        self.labels.commit(&file_name, &file_name)?;
+        self.manifest.closed(file_name.clone(), file_name, self.current_lines, self.current_bytes);
+        if let Some(compressor) = self.compressor {
+            if self.strategy == RotationStrategy::Numbered && compressor.delays() {
+                let delayed = self.dir.join(format!("{}.1", ACTIVE_FILE_NAME));
+                if delayed.exists() {
+                    compressor.compress(delayed);
+                }
+            }
+            compressor.finish()?;
+        }
+        if let Some(retention) = &mut self.retention {
+            retention.enforce(now)?;
+        }
+        self.labels.finish()?;
+        self.manifest.save(true)
+    }
 }
 
+/// Open a fresh, empty active file. It is opened for appending so writes land
+/// at the start again once copytruncate empties it.
+fn create_active(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.set_len(0)?;
+    Ok(file)
+}
+
+/// Where a numbered archive such as `app.log.3.gz` goes when archives shift
+/// up a number, or `None` for any other file.
+fn shifted_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let rest = name.strip_prefix(ACTIVE_FILE_NAME)?.strip_prefix('.')?;
+    let (number, extension) = match rest.split_once('.') {
+        Some((number, extension)) => (number, Some(extension)),
+        None => (rest, None),
+    };
+    let number: u32 = number.parse().ok()?;
+    let shifted = match extension {
+        Some(extension) => format!("{}.{}.{}", ACTIVE_FILE_NAME, number + 1, extension),
+        None => format!("{}.{}", ACTIVE_FILE_NAME, number + 1),
+    };
+    Some(path.with_file_name(shifted))
+}
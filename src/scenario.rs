@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::compression::Compression;
+use crate::entities::EntityPoolConfig;
+use crate::format::OutputFormat;
+use crate::generator::{GenerationMode, GeneratorConfig, ServiceSpec, Sink, default_service_names};
+use crate::incidents::Incident;
+use crate::latency::LatencyProfile;
+use crate::messages::LevelWeights;
+use crate::profiles::ServiceProfile;
+use crate::retention::RetentionPolicy;
+use crate::rotation::{RotationPolicy, RotationStrategy};
+use crate::traces::Topology;
+use crate::traffic::TrafficProfile;
+
+/// A complete generation job: what `POST /api/start` takes as JSON, and what
+/// scenario files describe in TOML, YAML or JSON.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Name a scenario is stored under by `POST /api/scenarios`.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Number of services with default names. Optional when `services` is given.
+    #[serde(default)]
+    num_services: u32,
+    /// Explicit service list with per-service weight, file size, format and
+    /// level weights, replacing the default names.
+    #[serde(default)]
+    services: Vec<ServiceSpec>,
+    /// Required in batch mode; ignored in live mode.
+    #[serde(default)]
+    total_size_mb: u64,
+    file_max_size_mb: u64,
+    /// When files rotate, e.g. `{"type": "size_or_time", "interval": "hourly"}`.
+    #[serde(default)]
+    rotation: RotationPolicy,
+    /// How files are archived: `"timestamped"`, `"create"`, `"copytruncate"`
+    /// or `"numbered"`.
+    #[serde(default)]
+    rotation_strategy: RotationStrategy,
+    /// Compress rotated archives: `"gzip"` or `"zstd"`.
+    #[serde(default)]
+    compression: Compression,
+    /// Leave the newest archive uncompressed until the next rotation.
+    #[serde(default)]
+    delay_compress: bool,
+    /// Limits on the archives each service keeps, e.g. `{"max_files": 10}`.
+    #[serde(default)]
+    retention: RetentionPolicy,
+    /// Per-service retention overrides.
+    #[serde(default)]
+    service_retention: HashMap<String, RetentionPolicy>,
+    #[serde(default)]
+    format: OutputFormat,
+    /// Per-service format overrides, e.g. `{"gateway-service": "combined"}`.
+    #[serde(default)]
+    service_formats: HashMap<String, OutputFormat>,
+    /// Optional subdirectory of the output root for this job.
+    #[serde(default)]
+    output_subdir: Option<String>,
+    /// Where lines are written: `"file"`, `"stdout"` or both. Defaults to
+    /// files only.
+    #[serde(default = "default_sinks")]
+    sinks: Vec<Sink>,
+    /// Seed for reproducible output.
+    #[serde(default)]
+    seed: Option<u64>,
+    /// Simulated time window (RFC 3339) and per-service event rate.
+    #[serde(default, deserialize_with = "deserialize_time")]
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    end_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    events_per_second: Option<f64>,
+    #[serde(default)]
+    mode: GenerationMode,
+    /// Live mode: per-service rate in bytes per second instead of events.
+    #[serde(default)]
+    bytes_per_second: Option<u64>,
+    /// Live mode: stop automatically after this many seconds.
+    #[serde(default)]
+    duration_seconds: Option<u64>,
+    /// Shape of the event rate over time, e.g. `{"type": "diurnal"}`.
+    #[serde(default)]
+    traffic_profile: TrafficProfile,
+    /// Per-service traffic profile overrides.
+    #[serde(default)]
+    service_traffic_profiles: HashMap<String, TrafficProfile>,
+    /// Scheduled incidents, e.g. an outage of one service 10 minutes in.
+    #[serde(default)]
+    incidents: Vec<Incident>,
+    /// Call graph for distributed traces, e.g. gateway -> auth -> order.
+    #[serde(default)]
+    topology: Option<Topology>,
+    /// Share of events (0.0–1.0) that start a start/end lifecycle pair.
+    #[serde(default)]
+    lifecycle_ratio: f64,
+    /// Job-wide user and tenant pools, e.g. `{"users": 5000, "tenants": 40}`.
+    #[serde(default)]
+    entities: Option<EntityPoolConfig>,
+    /// Relative level weights, e.g. `{"debug": 100, "info": 500}`. Levels left
+    /// out keep their defaults.
+    #[serde(default)]
+    level_weights: LevelWeights,
+    /// Per-service level weight overrides.
+    #[serde(default)]
+    service_level_weights: HashMap<String, LevelWeights>,
+    /// Latency models, e.g. `{"db": {"median_ms": 12, "p99_ms": 400}}`.
+    #[serde(default)]
+    latency: LatencyProfile,
+    /// Per-service latency overrides.
+    #[serde(default)]
+    service_latency: HashMap<String, LatencyProfile>,
+    /// Per-service profiles (category weights, messages, vocabulary),
+    /// replacing the built-in profile of that service.
+    #[serde(default)]
+    service_profiles: HashMap<String, ServiceProfile>,
+}
+
+/// Encoding of a scenario file.
+#[derive(Clone, Copy)]
+pub enum ScenarioFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ScenarioFormat {
+    /// Format of a file, from its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Format of an uploaded body, from its `Content-Type`.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/toml" | "text/toml" | "text/x-toml" => Some(Self::Toml),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => Some(Self::Yaml),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+impl Scenario {
+    pub fn parse(text: &str, format: ScenarioFormat) -> Result<Self, String> {
+        match format {
+            ScenarioFormat::Toml => toml::from_str(text).map_err(|e| e.to_string().trim_end().to_string()),
+            ScenarioFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+            ScenarioFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Read a scenario file; the extension (`.toml`, `.yaml`/`.yml`,
+    /// `.json`) selects the format.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let format = ScenarioFormat::from_path(path)
+            .ok_or_else(|| "scenario files must end in .toml, .yaml, .yml or .json".to_string())?;
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text, format)
+    }
+
+    /// Check the scenario and turn it into a job writing under `output_root`.
+    pub fn into_config(self, output_root: &Path) -> Result<GeneratorConfig, String> {
+        if !self.services.is_empty() && self.num_services != 0 && self.num_services as usize != self.services.len() {
+            return Err("num_services does not match the number of entries in services".into());
+        }
+        let num_services = if self.services.is_empty() {
+            self.num_services
+        } else {
+            self.services.len().min(u32::MAX as usize) as u32
+        };
+        if num_services == 0 || num_services > 1000 {
+            return Err("Number of services must be between 1 and 1000".into());
+        }
+        if self.mode == GenerationMode::Batch && self.total_size_mb == 0 {
+            return Err("Total size must be greater than 0".into());
+        }
+        if self.file_max_size_mb == 0 {
+            return Err("File max size must be greater than 0".into());
+        }
+
+        let service_names = if self.services.is_empty() {
+            default_service_names(num_services)
+        } else {
+            self.services.iter().map(|spec| spec.name.clone()).collect()
+        };
+        let overrides = [
+            ("service_formats", self.service_formats.keys().collect::<Vec<_>>()),
+            ("service_traffic_profiles", self.service_traffic_profiles.keys().collect()),
+            ("service_level_weights", self.service_level_weights.keys().collect()),
+            ("service_latency", self.service_latency.keys().collect()),
+            ("service_profiles", self.service_profiles.keys().collect()),
+            ("service_retention", self.service_retention.keys().collect()),
+        ];
+        for (field, names) in overrides {
+            if let Some(unknown) = names.into_iter().find(|name| !service_names.contains(name)) {
+                return Err(format!("Unknown service in {}: {}", field, unknown));
+            }
+        }
+
+        let output_dir = match &self.output_subdir {
+            Some(subdir) => resolve_subdir(output_root, subdir)
+                .ok_or("output_subdir must be a relative path inside the output directory")?,
+            None => output_root.to_path_buf(),
+        };
+
+        let mut config = GeneratorConfig {
+            num_services,
+            target_bytes: match self.mode {
+                GenerationMode::Batch => self.total_size_mb * 1024 * 1024,
+                GenerationMode::Live => 0,
+            },
+            file_max_bytes: self.file_max_size_mb * 1024 * 1024,
+            rotation: self.rotation,
+            rotation_strategy: self.rotation_strategy,
+            compression: self.compression,
+            delay_compress: self.delay_compress,
+            retention: self.retention,
+            service_retention: self.service_retention,
+            output_dir,
+            sinks: self.sinks,
+            service_names,
+            format: self.format,
+            service_formats: self.service_formats,
+            level_weights: self.level_weights,
+            service_level_weights: self.service_level_weights,
+            latency: self.latency,
+            service_latency: self.service_latency,
+            service_profiles: self.service_profiles,
+            service_weights: HashMap::new(),
+            service_file_max_bytes: HashMap::new(),
+            seed: self.seed,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            events_per_second: self.events_per_second,
+            mode: self.mode,
+            bytes_per_second: self.bytes_per_second,
+            duration_seconds: self.duration_seconds,
+            traffic_profile: self.traffic_profile,
+            service_traffic_profiles: self.service_traffic_profiles,
+            incidents: self.incidents,
+            topology: self.topology,
+            lifecycle_ratio: self.lifecycle_ratio,
+            entities: self.entities,
+        };
+        if !self.services.is_empty() {
+            config.set_services(self.services);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+fn default_sinks() -> Vec<Sink> {
+    vec![Sink::File]
+}
+
+/// Accept RFC 3339 strings, and native TOML datetimes so scenario files need
+/// not quote them.
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let text = match Option::<toml::Value>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(toml::Value::String(text)) => text,
+        Some(toml::Value::Datetime(datetime)) => datetime.to_string(),
+        Some(_) => return Err(serde::de::Error::custom("expected an RFC 3339 timestamp")),
+    };
+    DateTime::parse_from_rfc3339(&text)
+        .map(|time| Some(time.with_timezone(&Utc)))
+        .map_err(|e| serde::de::Error::custom(format!("invalid timestamp `{}` ({}); use RFC 3339 with a UTC offset", text, e)))
+}
+
+/// Join `subdir` onto `root`, rejecting absolute paths and `..` so a job can
+/// never write outside the configured output root.
+fn resolve_subdir(root: &Path, subdir: &str) -> Option<PathBuf> {
+    let subdir = Path::new(subdir);
+    let mut components = subdir.components().peekable();
+    components.peek()?;
+    if components.all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        Some(root.join(subdir))
+    } else {
+        None
+    }
+}
@@ -1,17 +1,27 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{Path as UrlPath, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::Html;
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use serde::{Deserialize, Serialize};
-
-use crate::generator::{GeneratorConfig, GeneratorState, start_generation};
-
-pub type AppState = Arc<GeneratorState>;
+use serde::Serialize;
+
+use crate::generator::{GenerationMode, GeneratorConfig, GeneratorState, start_generation};
+use crate::scenario::{Scenario, ScenarioFormat};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub generator: Arc<GeneratorState>,
+    /// Root directory every job writes under.
+    pub output_root: Arc<PathBuf>,
+    /// Scenarios uploaded through `POST /api/scenarios`, keyed by name.
+    pub scenarios: Arc<Mutex<BTreeMap<String, Scenario>>>,
+}
 
 pub fn create_router(state: AppState) -> Router {
     Router::new()
@@ -19,6 +29,8 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/start", post(start_handler))
         .route("/api/stop", post(stop_handler))
         .route("/api/progress", get(progress_handler))
+        .route("/api/scenarios", get(list_scenarios_handler).post(upload_scenario_handler))
+        .route("/api/scenarios/:name/start", post(start_scenario_handler))
         .with_state(state)
 }
 
@@ -26,13 +38,6 @@ async fn index_page() -> Html<&'static str> {
     Html(include_str!("index.html"))
 }
 
-#[derive(Deserialize)]
-struct StartRequest {
-    num_services: u32,
-    total_size_mb: u64,
-    file_max_size_mb: u64,
-}
-
 #[derive(Serialize)]
 struct StartResponse {
     message: String,
@@ -42,10 +47,24 @@ struct StartResponse {
 struct ProgressResponse {
     running: bool,
     bytes_written: u64,
+    /// What the written logs take on disk, after archive compression.
+    disk_bytes: u64,
     target_bytes: u64,
-    percent: f64,
+    /// Completion percentage in batch mode; `null` in live mode, which has no target.
+    percent: Option<f64>,
+    live: bool,
+    events_written: u64,
+    bytes_per_second: f64,
+    events_per_second: f64,
     services_total: u64,
     services_done: u64,
+    services_failed: u64,
+}
+
+#[derive(Serialize)]
+struct ScenarioSummary {
+    name: String,
+    description: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -55,36 +74,76 @@ struct ErrorResponse {
 
 async fn start_handler(
     State(state): State<AppState>,
-    Json(req): Json<StartRequest>,
+    Json(scenario): Json<Scenario>,
 ) -> Result<Json<StartResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate
-    if req.num_services == 0 || req.num_services > 1000 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Number of services must be between 1 and 1000".into(),
-            }),
-        ));
-    }
-    if req.total_size_mb == 0 {
+    let config = scenario.into_config(&state.output_root).map_err(bad_request)?;
+    start_job(&state, config)
+}
+
+/// Store an uploaded scenario file under its `name`, replacing any scenario of
+/// that name. The body's `Content-Type` selects TOML, YAML or JSON.
+async fn upload_scenario_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<StartResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let Some(format) = ScenarioFormat::from_content_type(content_type) else {
         return Err((
-            StatusCode::BAD_REQUEST,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Json(ErrorResponse {
-                error: "Total size must be greater than 0".into(),
+                error: "Scenarios must be sent as application/toml, application/yaml or application/json".into(),
             }),
         ));
-    }
-    if req.file_max_size_mb == 0 {
+    };
+    let scenario = Scenario::parse(&body, format).map_err(|e| bad_request(format!("Invalid scenario: {}", e)))?;
+    let name = match scenario.name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => return Err(bad_request("Uploaded scenarios need a name".into())),
+    };
+    scenario
+        .clone()
+        .into_config(&state.output_root)
+        .map_err(|e| bad_request(format!("Invalid scenario {}: {}", name, e)))?;
+
+    state.scenarios.lock().unwrap().insert(name.clone(), scenario);
+    Ok(Json(StartResponse {
+        message: format!("Scenario {} saved", name),
+    }))
+}
+
+async fn list_scenarios_handler(State(state): State<AppState>) -> Json<Vec<ScenarioSummary>> {
+    let scenarios = state.scenarios.lock().unwrap();
+    Json(
+        scenarios
+            .iter()
+            .map(|(name, scenario)| ScenarioSummary {
+                name: name.clone(),
+                description: scenario.description.clone(),
+            })
+            .collect(),
+    )
+}
+
+async fn start_scenario_handler(
+    State(state): State<AppState>,
+    UrlPath(name): UrlPath<String>,
+) -> Result<Json<StartResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(scenario) = state.scenarios.lock().unwrap().get(&name).cloned() else {
         return Err((
-            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: "File max size must be greater than 0".into(),
+                error: format!("Unknown scenario: {}", name),
             }),
         ));
-    }
+    };
+    let config = scenario.into_config(&state.output_root).map_err(bad_request)?;
+    start_job(&state, config)
+}
 
+fn start_job(state: &AppState, config: GeneratorConfig) -> Result<Json<StartResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Check if already running
-    if state.running.load(Ordering::SeqCst) {
+    if state.generator.running.load(Ordering::SeqCst) {
         return Err((
             StatusCode::CONFLICT,
             Json(ErrorResponse {
@@ -93,73 +152,53 @@ async fn start_handler(
         ));
     }
 
-    let target_bytes = req.total_size_mb * 1024 * 1024;
-    let file_max_bytes = req.file_max_size_mb * 1024 * 1024;
-
-    // Generate service names that mimic real microservices
-    let service_name_templates = vec![
-        "auth-service", "user-service", "order-service", "payment-service",
-        "inventory-service", "notification-service", "search-service",
-        "analytics-service", "gateway-service", "billing-service",
-        "shipping-service", "catalog-service", "review-service",
-        "recommendation-service", "email-service", "scheduler-service",
-        "config-service", "audit-service", "report-service", "cache-service",
-        "media-service", "webhook-service", "export-service", "import-service",
-        "monitoring-service", "logging-service", "discovery-service",
-        "rate-limiter-service", "session-service", "tenant-service",
-    ];
-
-    let service_names: Vec<String> = (0..req.num_services)
-        .map(|i| {
-            if (i as usize) < service_name_templates.len() {
-                service_name_templates[i as usize].to_string()
-            } else {
-                format!("microservice-{}", i + 1)
-            }
-        })
-        .collect();
-
-    let config = GeneratorConfig {
-        num_services: req.num_services,
-        target_bytes,
-        file_max_bytes,
-        output_dir: PathBuf::from("logs"),
-        service_names,
-    };
-
-    start_generation(config, Arc::clone(&state));
-
-    Ok(Json(StartResponse {
-        message: format!(
+    let message = match config.mode {
+        GenerationMode::Batch => format!(
             "Started generating {} MB of logs across {} services",
-            req.total_size_mb, req.num_services
+            config.target_bytes / (1024 * 1024),
+            config.num_services
         ),
-    }))
+        GenerationMode::Live => format!("Started live generation across {} services", config.num_services),
+    };
+    start_generation(config, Arc::clone(&state.generator));
+    Ok(Json(StartResponse { message }))
+}
+
+fn bad_request(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
 }
 
 async fn stop_handler(State(state): State<AppState>) -> Json<StartResponse> {
-    state.cancel.store(true, Ordering::SeqCst);
+    state.generator.cancel.store(true, Ordering::SeqCst);
     Json(StartResponse {
         message: "Stop signal sent. Generation will halt shortly.".into(),
     })
 }
 
 async fn progress_handler(State(state): State<AppState>) -> Json<ProgressResponse> {
-    let bytes_written = state.bytes_written.load(Ordering::Relaxed);
-    let target = state.target_bytes.load(Ordering::Relaxed);
+    let bytes_written = state.generator.bytes_written.load(Ordering::Relaxed);
+    let target = state.generator.target_bytes.load(Ordering::Relaxed);
+    let live = state.generator.live.load(Ordering::SeqCst);
     let percent = if target > 0 {
         (bytes_written as f64 / target as f64) * 100.0
     } else {
         0.0
     };
+    let (bytes_per_second, events_per_second) = state.generator.throughput();
 
     Json(ProgressResponse {
-        running: state.running.load(Ordering::SeqCst),
+        running: state.generator.running.load(Ordering::SeqCst),
         bytes_written,
+        disk_bytes: state.generator.disk_bytes.load(Ordering::Relaxed),
         target_bytes: target,
-        percent: (percent * 100.0).round() / 100.0, // 2 decimal places
-        services_total: state.services_total.load(Ordering::SeqCst),
-        services_done: state.services_done.load(Ordering::SeqCst),
+        percent: (!live).then(|| (percent * 100.0).round() / 100.0), // 2 decimal places
+        live,
+        events_written: state.generator.events_written.load(Ordering::Relaxed),
+        bytes_per_second: bytes_per_second.round(),
+        events_per_second: (events_per_second * 10.0).round() / 10.0,
+        services_total: state.generator.services_total.load(Ordering::SeqCst),
+        services_done: state.generator.services_done.load(Ordering::SeqCst),
+        services_failed: state.generator.services_failed.load(Ordering::SeqCst),
     })
 }
 
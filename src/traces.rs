@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::entities::EntityPool;
+use crate::messages::{self, EventQueue, FieldValue, LogEvent, LogLevel, ServiceContext};
+use crate::traffic::TrafficProfile;
+
+/// Service call graph used to produce distributed traces that span several
+/// services' logs.
+#[derive(Clone, Deserialize)]
+pub struct Topology {
+    /// Service that receives external requests; every trace starts here.
+    pub entry: String,
+    /// Downstream services each service calls, in call order.
+    #[serde(default)]
+    pub calls: HashMap<String, Vec<String>>,
+    /// Traced requests entering `entry` per second of (simulated or wall-clock) time.
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+}
+
+fn default_requests_per_second() -> f64 {
+    1.0
+}
+
+impl Topology {
+    pub fn validate(&self, service_names: &[String]) -> Result<(), String> {
+        if !(self.requests_per_second > 0.0 && self.requests_per_second.is_finite()) {
+            return Err("topology.requests_per_second must be greater than 0".into());
+        }
+        let mut names = std::iter::once(&self.entry)
+            .chain(self.calls.keys())
+            .chain(self.calls.values().flatten());
+        if let Some(unknown) = names.find(|name| !service_names.contains(name)) {
+            return Err(format!("Unknown service in topology: {}", unknown));
+        }
+        self.check_acyclic(&self.entry, &mut Vec::new())
+    }
+
+    fn check_acyclic<'a>(&'a self, service: &'a str, path: &mut Vec<&'a str>) -> Result<(), String> {
+        if path.contains(&service) {
+            return Err(format!("topology has a call cycle through {}", service));
+        }
+        path.push(service);
+        for callee in self.callees(service) {
+            self.check_acyclic(callee, path)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    fn callees(&self, service: &str) -> &[String] {
+        self.calls.get(service).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether requests entering `entry` ever reach `service`.
+    pub fn involves(&self, service: &str) -> bool {
+        fn reaches(topology: &Topology, from: &str, service: &str) -> bool {
+            from == service || topology.callees(from).iter().any(|c| reaches(topology, c, service))
+        }
+        reaches(self, &self.entry, service)
+    }
+}
+
+/// Share of spans that fail on their own; their callers then fail with 502.
+const SPAN_FAILURE_RATE: f64 = 0.005;
+
+/// One service's part of a trace, logged when the span ends.
+struct PendingSpan {
+    end: DateTime<Utc>,
+    start: DateTime<Utc>,
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    status: u16,
+    /// User the whole trace is attributed to, when the job has an entity pool.
+    user: Option<usize>,
+}
+
+/// Replays the job-wide stream of traced requests and queues the spans that
+/// belong to one service.
+///
+/// Every service builds the same traces from the same seed, so their lines
+/// share trace ids and parent/child span ids without any coordination
+/// between service threads.
+pub struct TraceFeed {
+    topology: Topology,
+    service: String,
+    seed: u64,
+    arrivals: SmallRng,
+    profile: TrafficProfile,
+    /// Arrival of the next request, or `None` once arrivals have run past the
+    /// end of the representable time range (at a very low request rate).
+    next_root: Option<DateTime<Utc>>,
+    index: u64,
+    entities: Option<Arc<EntityPool>>,
+}
+
+impl TraceFeed {
+    /// Follow the traces of `topology` for `service`, with the first request
+    /// arriving at `job_start`. `seed` must be the same for every service.
+    pub fn new(
+        topology: Topology,
+        service: &str,
+        seed: u64,
+        job_start: DateTime<Utc>,
+        profile: TrafficProfile,
+        entities: Option<Arc<EntityPool>>,
+    ) -> Self {
+        Self {
+            topology,
+            service: service.to_string(),
+            seed,
+            arrivals: SmallRng::seed_from_u64(seed),
+            profile,
+            next_root: Some(job_start),
+            index: 0,
+            entities,
+        }
+    }
+
+    /// Queue this service's spans of every request that arrived at or before
+    /// `t`. Each span is logged when it ends.
+    pub fn schedule_until<'a>(
+        &mut self,
+        rng: &mut impl Rng,
+        ctx: &ServiceContext<'a>,
+        t: DateTime<Utc>,
+        access_log: bool,
+        queue: &mut EventQueue<'a>,
+    ) {
+        let mut spans = Vec::new();
+        while let Some(root) = self.next_root.filter(|root| *root <= t) {
+            self.expand_root(root, &mut spans);
+        }
+        for span in spans {
+            let user = span.user;
+            let mut event = span_event(rng, ctx, span, access_log);
+            if let (Some(pool), Some(user)) = (&self.entities, user) {
+                pool.apply(user, &mut event);
+            }
+            queue.push(event);
+        }
+    }
+
+    fn expand_root(&mut self, root: DateTime<Utc>, spans: &mut Vec<PendingSpan>) {
+        let mut rng = SmallRng::seed_from_u64(self.seed ^ self.index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let trace_id = messages::random_trace_id(&mut rng);
+        let user = self.entities.as_ref().map(|pool| pool.sample(&mut rng));
+        let mut visit = Visit {
+            topology: &self.topology,
+            service: &self.service,
+            trace_id: &trace_id,
+            user,
+            rng: &mut rng,
+            out: spans,
+        };
+        visit.span(&self.topology.entry, None, root);
+
+        let u: f64 = self.arrivals.gen_range(f64::EPSILON..1.0);
+        let mean = 1_000_000.0 / self.topology.requests_per_second / self.profile.multiplier(root);
+        self.next_root = root.checked_add_signed(Duration::microseconds(((-u.ln() * mean) as i64).max(1)));
+        self.index += 1;
+    }
+}
+
+/// Walk of one trace's call tree, collecting the spans of a single service.
+struct Visit<'a, R> {
+    topology: &'a Topology,
+    service: &'a str,
+    trace_id: &'a str,
+    user: Option<usize>,
+    rng: &'a mut R,
+    out: &'a mut Vec<PendingSpan>,
+}
+
+impl<R: Rng> Visit<'_, R> {
+    /// Time the span of `service` starting at `start` and its calls. Returns
+    /// the span's end and whether it failed.
+    fn span(&mut self, service: &str, parent_span_id: Option<&str>, start: DateTime<Utc>) -> (DateTime<Utc>, bool) {
+        let span_id = messages::random_span_id(self.rng);
+        let callees = self.topology.callees(service);
+        let mut cursor = start + micros(self.rng.gen_range(500..5_000));
+        let mut callee_failed = false;
+        for callee in callees {
+            // Network hop there and back
+            let call_start = cursor + micros(self.rng.gen_range(200..2_000));
+            let (end, failed) = self.span(callee, Some(&span_id), call_start);
+            cursor = end + micros(self.rng.gen_range(200..2_000));
+            callee_failed |= failed;
+        }
+        let own_work = if callees.is_empty() { 2_000..50_000 } else { 1_000..20_000 };
+        let end = cursor + micros(self.rng.gen_range(own_work));
+        let failed = self.rng.gen_bool(SPAN_FAILURE_RATE);
+
+        if service == self.service {
+            self.out.push(PendingSpan {
+                end,
+                start,
+                trace_id: self.trace_id.to_string(),
+                span_id,
+                parent_span_id: parent_span_id.map(str::to_string),
+                status: match (failed, callee_failed) {
+                    (true, _) => 500,
+                    (false, true) => 502,
+                    (false, false) => 200,
+                },
+                user: self.user,
+            });
+        }
+        (end, failed || callee_failed)
+    }
+}
+
+fn micros(n: i64) -> Duration {
+    Duration::microseconds(n)
+}
+
+fn span_event<'a>(rng: &mut impl Rng, ctx: &ServiceContext<'a>, span: PendingSpan, access_log: bool) -> LogEvent<'a> {
+    let (level, template) = match span.status {
+        500 => (LogLevel::Error, "Request processing failed"),
+        502 => (LogLevel::Error, "External service call failed"),
+        _ => (LogLevel::Info, "Request completed successfully"),
+    };
+    let mut event = if access_log {
+        messages::generate_access_event(rng, level, ctx, span.end)
+    } else {
+        messages::generate_event_as(rng, level, ctx, span.end, "http", template)
+    };
+    event.trace_id = span.trace_id;
+    event.span_id = span.span_id;
+    event.set_field("status", span.status.into());
+    event.set_field("latency_ms", (span.end - span.start).num_milliseconds().into());
+    if let Some(parent) = span.parent_span_id {
+        event.set_field("parent_span_id", FieldValue::Str(parent));
+    }
+    event
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use serde::Deserialize;
+
+/// Lowest rate multiplier applied to a clock, so a profile that drops to zero
+/// makes a service very quiet instead of stalling it forever.
+const MIN_MULTIPLIER: f64 = 0.001;
+
+/// Shape of a service's event rate over the day and week. The multiplier is
+/// relative to the configured (peak) rate; times are evaluated in UTC.
+#[derive(Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrafficProfile {
+    /// Constant rate.
+    #[default]
+    Flat,
+    /// Sine wave over 24 hours, highest at `peak_hour`.
+    Diurnal {
+        #[serde(default = "default_peak_hour")]
+        peak_hour: f64,
+        #[serde(default = "default_trough_ratio")]
+        trough_ratio: f64,
+    },
+    /// Full rate on weekdays between `start_hour` and `end_hour`, reduced otherwise.
+    BusinessHours {
+        #[serde(default = "default_start_hour")]
+        start_hour: u32,
+        #[serde(default = "default_end_hour")]
+        end_hour: u32,
+        #[serde(default = "default_off_hours_ratio")]
+        off_hours_ratio: f64,
+    },
+    /// Diurnal shape on every day, scaled down on Saturdays and Sundays.
+    WeekendDip {
+        #[serde(default = "default_weekend_ratio")]
+        weekend_ratio: f64,
+        #[serde(default = "default_peak_hour")]
+        peak_hour: f64,
+        #[serde(default = "default_trough_ratio")]
+        trough_ratio: f64,
+    },
+    /// Piecewise-linear curve through `(hour, multiplier)` points, wrapping
+    /// around midnight.
+    Custom { points: Vec<CurvePoint> },
+}
+
+#[derive(Clone, Deserialize)]
+pub struct CurvePoint {
+    pub hour: f64,
+    pub multiplier: f64,
+}
+
+fn default_peak_hour() -> f64 {
+    14.0
+}
+
+fn default_trough_ratio() -> f64 {
+    0.2
+}
+
+fn default_start_hour() -> u32 {
+    9
+}
+
+fn default_end_hour() -> u32 {
+    17
+}
+
+fn default_off_hours_ratio() -> f64 {
+    0.15
+}
+
+fn default_weekend_ratio() -> f64 {
+    0.4
+}
+
+impl TrafficProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        let ratio_ok = |r: f64| (0.0..=1.0).contains(&r);
+        let hour_ok = |h: f64| (0.0..24.0).contains(&h);
+        match self {
+            TrafficProfile::Flat => Ok(()),
+            TrafficProfile::Diurnal { peak_hour, trough_ratio } => {
+                if !hour_ok(*peak_hour) || !ratio_ok(*trough_ratio) {
+                    return Err("diurnal profile needs peak_hour in 0..24 and trough_ratio in 0..1".into());
+                }
+                Ok(())
+            }
+            TrafficProfile::BusinessHours { start_hour, end_hour, off_hours_ratio } => {
+                if start_hour >= end_hour || *end_hour > 24 || !ratio_ok(*off_hours_ratio) {
+                    return Err("business_hours profile needs start_hour < end_hour <= 24 and off_hours_ratio in 0..1".into());
+                }
+                Ok(())
+            }
+            TrafficProfile::WeekendDip { weekend_ratio, peak_hour, trough_ratio } => {
+                if !ratio_ok(*weekend_ratio) || !hour_ok(*peak_hour) || !ratio_ok(*trough_ratio) {
+                    return Err("weekend_dip profile needs weekend_ratio and trough_ratio in 0..1 and peak_hour in 0..24".into());
+                }
+                Ok(())
+            }
+            TrafficProfile::Custom { points } => {
+                if points.is_empty() {
+                    return Err("custom profile needs at least one point".into());
+                }
+                if !points.iter().all(|p| hour_ok(p.hour) && p.multiplier >= 0.0 && p.multiplier.is_finite()) {
+                    return Err("custom profile points need hour in 0..24 and a non-negative multiplier".into());
+                }
+                if points.windows(2).any(|w| w[0].hour >= w[1].hour) {
+                    return Err("custom profile points must be in ascending hour order".into());
+                }
+                if points.iter().all(|p| p.multiplier == 0.0) {
+                    return Err("custom profile needs at least one point with a multiplier above 0".into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_flat(&self) -> bool {
+        matches!(self, TrafficProfile::Flat)
+    }
+
+    /// Rate multiplier at time `t`, never below `MIN_MULTIPLIER`.
+    pub fn multiplier(&self, t: DateTime<Utc>) -> f64 {
+        let hour = t.hour() as f64 + t.minute() as f64 / 60.0 + t.second() as f64 / 3600.0;
+        let weekend = matches!(t.weekday(), Weekday::Sat | Weekday::Sun);
+        let m = match self {
+            TrafficProfile::Flat => 1.0,
+            TrafficProfile::Diurnal { peak_hour, trough_ratio } => diurnal(hour, *peak_hour, *trough_ratio),
+            TrafficProfile::BusinessHours { start_hour, end_hour, off_hours_ratio } => {
+                if !weekend && hour >= *start_hour as f64 && hour < *end_hour as f64 {
+                    1.0
+                } else {
+                    *off_hours_ratio
+                }
+            }
+            TrafficProfile::WeekendDip { weekend_ratio, peak_hour, trough_ratio } => {
+                let day = if weekend { *weekend_ratio } else { 1.0 };
+                day * diurnal(hour, *peak_hour, *trough_ratio)
+            }
+            TrafficProfile::Custom { points } => piecewise(points, hour),
+        };
+        m.max(MIN_MULTIPLIER)
+    }
+
+    /// Integral of the multiplier over `[from, to]`, in microseconds. This
+    /// is how many "full-rate" microseconds the window is worth.
+    pub fn weighted_micros(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        let total = (to - from).num_microseconds().unwrap_or(i64::MAX).max(0) as f64;
+        if self.is_flat() || total == 0.0 {
+            return total;
+        }
+        // Midpoint rule with 15-minute steps, or 100 steps for short windows
+        let step = (total / 100.0).min(15.0 * 60.0 * 1_000_000.0);
+        let steps = (total / step).ceil() as i64;
+        let step = total / steps as f64;
+        (0..steps)
+            .map(|i| {
+                let mid = from + Duration::microseconds(((i as f64 + 0.5) * step) as i64);
+                self.multiplier(mid) * step
+            })
+            .sum()
+    }
+}
+
+fn diurnal(hour: f64, peak_hour: f64, trough_ratio: f64) -> f64 {
+    let phase = (hour - peak_hour) / 24.0 * std::f64::consts::TAU;
+    trough_ratio + (1.0 - trough_ratio) * (1.0 + phase.cos()) / 2.0
+}
+
+/// `points` are validated to be sorted by hour.
+fn piecewise(points: &[CurvePoint], hour: f64) -> f64 {
+    let first = &points[0];
+    let last = &points[points.len() - 1];
+
+    // Find the surrounding points, wrapping around midnight
+    let (from, to, from_hour, to_hour) = match points.iter().position(|p| p.hour > hour) {
+        Some(0) => (last, first, last.hour - 24.0, first.hour),
+        Some(i) => (&points[i - 1], &points[i], points[i - 1].hour, points[i].hour),
+        None => (last, first, last.hour, first.hour + 24.0),
+    };
+    if to_hour <= from_hour {
+        return from.multiplier;
+    }
+    let f = (hour - from_hour) / (to_hour - from_hour);
+    from.multiplier + (to.multiplier - from.multiplier) * f
+}